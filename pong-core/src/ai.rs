@@ -0,0 +1,263 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    ARENA_HEIGHT, BALL_SIZE, Ball, GameState, PADDLE_WIDTH, PaddleInput, Rng, Side,
+    paddle::PADDLE_SPEED,
+};
+
+/// How close to its target the paddle must be before the computer stops moving it, in arena
+/// units.
+const DEAD_ZONE: f64 = 1.0;
+
+/// Preset strengths for the computer opponent.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Impossible,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 4] = [Self::Easy, Self::Normal, Self::Hard, Self::Impossible];
+
+    pub fn settings(self) -> AiSettings {
+        match self {
+            Self::Easy => AiSettings {
+                reaction_delay: 0.4,
+                max_speed: 0.4,
+                aim_error: 20.0,
+            },
+            Self::Normal => AiSettings {
+                reaction_delay: 0.25,
+                max_speed: 0.6,
+                aim_error: 14.0,
+            },
+            Self::Hard => AiSettings {
+                reaction_delay: 0.12,
+                max_speed: 0.8,
+                aim_error: 10.0,
+            },
+            Self::Impossible => AiSettings {
+                reaction_delay: 0.0,
+                max_speed: 1.0,
+                aim_error: 0.0,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Easy => "Easy",
+            Self::Normal => "Normal",
+            Self::Hard => "Hard",
+            Self::Impossible => "Impossible",
+        })
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown difficulty {name:?}"))
+    }
+}
+
+/// What limits the computer opponent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AiSettings {
+    /// Seconds between looking at the ball and deciding where to go.
+    pub reaction_delay: f64,
+    /// Top paddle speed, as a fraction of the speed available to human players.
+    pub max_speed: f64,
+    /// Largest distance, in arena units, by which the predicted intercept may be missed.
+    pub aim_error: f64,
+}
+
+impl Default for AiSettings {
+    fn default() -> Self {
+        Difficulty::default().settings()
+    }
+}
+
+/// A computer player driving one paddle.
+///
+/// Every `reaction_delay` seconds it predicts where the ball will cross its paddle, bounces off
+/// the walls included, and heads there; the rest of the time it keeps chasing its last decision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ai {
+    side: Side,
+    settings: AiSettings,
+    rng: Rng,
+    /// Seconds until the next decision.
+    reaction: f64,
+    /// Where the centre of the paddle should go.
+    target: Option<f64>,
+}
+
+impl Ai {
+    /// `rng` should come from [`GameState::side_rng`], to keep the match reproducible.
+    pub fn new(side: Side, settings: AiSettings, rng: Rng) -> Self {
+        Self {
+            side,
+            settings,
+            rng,
+            reaction: 0.0,
+            target: None,
+        }
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// Decides how to move the paddle for the next `dt` seconds of `state`.
+    pub fn input(&mut self, state: &GameState, dt: f64) -> PaddleInput {
+        self.reaction -= dt;
+        if self.reaction <= 0.0 {
+            self.reaction = self.settings.reaction_delay;
+            self.target = Some(self.decide(state));
+        }
+
+        let paddle = state.paddle(self.side);
+        let Some(target) = self.target else {
+            return PaddleInput::Idle;
+        };
+        let distance = target - paddle.center();
+        if distance.abs() <= DEAD_ZONE {
+            return PaddleInput::Idle;
+        }
+
+        // don't overshoot the target within a single step
+        let needed = distance.abs() / (PADDLE_SPEED * dt);
+        let speed = self.settings.max_speed.min(needed);
+        PaddleInput::Move(speed.copysign(distance))
+    }
+
+    /// Picks where the paddle's centre should be, with some error depending on the settings.
+    fn decide(&mut self, state: &GameState) -> f64 {
+        let paddle = state.paddle(self.side);
+        let approaching = match self.side {
+            Side::Left => state.ball().vx < 0.0,
+            Side::Right => state.ball().vx > 0.0,
+        };
+        if state.serve().is_some() || !approaching {
+            return ARENA_HEIGHT / 2.0;
+        }
+
+        let face = match self.side {
+            Side::Left => paddle.x + PADDLE_WIDTH,
+            Side::Right => paddle.x - BALL_SIZE,
+        };
+        let error = self
+            .rng
+            .range(-self.settings.aim_error, self.settings.aim_error);
+        predict_intercept(state.ball(), face).unwrap_or(ARENA_HEIGHT / 2.0) + error
+    }
+}
+
+/// Where the centre of `ball` will be when its left edge reaches `x`, reflecting off the top and
+/// bottom of the arena on the way.
+///
+/// Returns `None` if the ball is moving away from `x`.
+pub fn predict_intercept(ball: &Ball, x: f64) -> Option<f64> {
+    let time = (x - ball.x) / ball.vx;
+    if !time.is_finite() || time < 0.0 {
+        return None;
+    }
+
+    // unfold the reflections: the path repeats every two arena heights
+    let bottom = ARENA_HEIGHT - BALL_SIZE;
+    let period = 2.0 * bottom;
+    let y = (ball.y + ball.vy * time).rem_euclid(period);
+    let y = if y > bottom { period - y } else { y };
+    Some(y + BALL_SIZE / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Where a ball's centre is when its top edge is at `y`.
+    fn centre(y: f64) -> f64 {
+        y + BALL_SIZE / 2.0
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("the ball should reach the paddle");
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn a_straight_shot_arrives_where_it_is_aimed() {
+        let ball = Ball {
+            x: 80.0,
+            y: 30.0,
+            vx: 60.0,
+            vy: 0.0,
+        };
+        assert_close(predict_intercept(&ball, 140.0), centre(30.0));
+
+        let ball = Ball {
+            vx: -60.0,
+            vy: 20.0,
+            ..ball
+        };
+        // a second to get there, falling 20 units without reaching the bottom
+        assert_close(predict_intercept(&ball, 20.0), centre(50.0));
+    }
+
+    #[test]
+    fn one_bounce_is_reflected() {
+        let bottom = ARENA_HEIGHT - BALL_SIZE;
+        let ball = Ball {
+            x: 0.0,
+            y: 10.0,
+            vx: 10.0,
+            vy: -20.0,
+        };
+        // falls 20 units, 10 of them back down after bouncing off the top
+        assert_close(predict_intercept(&ball, 10.0), centre(10.0));
+
+        let ball = Ball {
+            y: bottom - 5.0,
+            vy: 20.0,
+            ..ball
+        };
+        assert_close(predict_intercept(&ball, 10.0), centre(bottom - 15.0));
+    }
+
+    #[test]
+    fn several_bounces_are_reflected() {
+        let bottom = ARENA_HEIGHT - BALL_SIZE;
+        let ball = Ball {
+            x: 0.0,
+            y: 0.0,
+            vx: 1.0,
+            vy: bottom,
+        };
+        // down, up, down, and a quarter of the way back up
+        assert_close(predict_intercept(&ball, 3.25), centre(bottom * 0.75));
+        // a whole number of trips down and back lands where it started
+        assert_close(predict_intercept(&ball, 4.0), centre(0.0));
+    }
+
+    #[test]
+    fn a_ball_moving_away_never_arrives() {
+        let ball = Ball {
+            x: 80.0,
+            y: 30.0,
+            vx: 60.0,
+            vy: 10.0,
+        };
+        assert_eq!(predict_intercept(&ball, 20.0), None);
+        assert_eq!(predict_intercept(&Ball { vx: 0.0, ..ball }, 20.0), None);
+    }
+}
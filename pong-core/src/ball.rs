@@ -0,0 +1,117 @@
+use crate::{ARENA_HEIGHT, ARENA_WIDTH, PADDLE_WIDTH, Paddle, Side};
+
+/// Width and height of the ball, in arena units.
+pub const BALL_SIZE: f64 = 2.0;
+
+/// Speed of a freshly served ball at normal speed, in arena units per second.
+pub const BALL_SPEED: f64 = 60.0;
+
+/// Range of angles from the horizontal, either way, that the ball is served at, in radians.
+pub const MIN_SERVE_ANGLE: f64 = 0.2;
+pub const MAX_SERVE_ANGLE: f64 = 0.5;
+
+/// Factor applied to the ball's speed on every paddle hit.
+const BALL_SPEEDUP: f64 = 1.05;
+
+/// Fastest the ball may travel at normal speed, in arena units per second.
+pub const MAX_BALL_SPEED: f64 = 180.0;
+
+/// Steepest angle the ball leaves a paddle at, when hit on its very edge.
+const MAX_BOUNCE_ANGLE: f64 = std::f64::consts::FRAC_PI_3;
+
+/// The ball, positioned by its top-left corner in arena units.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Ball {
+    pub x: f64,
+    pub y: f64,
+    pub vx: f64,
+    pub vy: f64,
+}
+
+impl Ball {
+    /// Places a ball in the middle of the arena, heading toward `side` at `speed` arena units
+    /// per second and `angle` radians below the horizontal.
+    pub fn serve(side: Side, speed: f64, angle: f64) -> Self {
+        let direction = match side {
+            Side::Left => -1.0,
+            Side::Right => 1.0,
+        };
+        Self {
+            x: (ARENA_WIDTH - BALL_SIZE) / 2.0,
+            y: (ARENA_HEIGHT - BALL_SIZE) / 2.0,
+            vx: direction * speed * angle.cos(),
+            vy: speed * angle.sin(),
+        }
+    }
+
+    /// Moves the ball by `dt` seconds, bouncing it off the top and bottom of the arena.
+    pub fn update(&mut self, dt: f64) {
+        self.x += self.vx * dt;
+        self.y += self.vy * dt;
+
+        let bottom = ARENA_HEIGHT - BALL_SIZE;
+        if self.y < 0.0 {
+            self.y = -self.y;
+            self.vy = self.vy.abs();
+        } else if self.y > bottom {
+            self.y = 2.0 * bottom - self.y;
+            self.vy = -self.vy.abs();
+        }
+        self.y = self.y.clamp(0.0, bottom);
+    }
+
+    /// The side whose goal the ball has passed, if it has left the arena horizontally.
+    pub fn out_side(&self) -> Option<Side> {
+        if self.x < 0.0 {
+            Some(Side::Left)
+        } else if self.x > ARENA_WIDTH - BALL_SIZE {
+            Some(Side::Right)
+        } else {
+            None
+        }
+    }
+
+    /// Bounces the ball off `paddle` if it crossed the paddle's face during the last move.
+    ///
+    /// The outgoing angle depends on where the ball struck: dead centre sends it straight back,
+    /// the edges send it off at up to [`MAX_BOUNCE_ANGLE`], a little faster each time but never
+    /// above `max_speed`. Returns whether the ball was hit.
+    pub fn collide(&mut self, paddle: &Paddle, previous_x: f64, max_speed: f64) -> bool {
+        let overlaps = self.y + BALL_SIZE > paddle.y && self.y < paddle.y + paddle.height;
+        if !overlaps {
+            return false;
+        }
+
+        let direction = match paddle.side {
+            Side::Left => {
+                let face = paddle.x + PADDLE_WIDTH;
+                if self.vx >= 0.0 || previous_x < face || self.x >= face {
+                    return false;
+                }
+                self.x = 2.0 * face - self.x;
+                1.0
+            }
+            Side::Right => {
+                let face = paddle.x - BALL_SIZE;
+                if self.vx <= 0.0 || previous_x > face || self.x <= face {
+                    return false;
+                }
+                self.x = 2.0 * face - self.x;
+                -1.0
+            }
+        };
+
+        let half = (paddle.height + BALL_SIZE) / 2.0;
+        let offset = ((self.y + BALL_SIZE / 2.0 - paddle.center()) / half).clamp(-1.0, 1.0);
+        let angle = offset * MAX_BOUNCE_ANGLE;
+        let speed = (self.speed() * BALL_SPEEDUP).min(max_speed);
+        self.vx = direction * speed * angle.cos();
+        self.vy = speed * angle.sin();
+        true
+    }
+
+    /// Speed of the ball, in arena units per second.
+    pub fn speed(&self) -> f64 {
+        self.vx.hypot(self.vy)
+    }
+}
@@ -0,0 +1,41 @@
+//! The pong simulation, free of any terminal or rendering concerns.
+//!
+//! Drive a [`GameState`] by calling [`GameState::step`] with each player's [`Inputs`] and a time
+//! step; read the results back through its accessors.
+
+mod ai;
+mod ball;
+mod link;
+mod paddle;
+mod protocol;
+mod replay;
+mod rng;
+mod rollback;
+mod score;
+mod state;
+mod stats;
+mod wire;
+
+pub use ai::{Ai, AiSettings, Difficulty, predict_intercept};
+pub use ball::{BALL_SIZE, BALL_SPEED, Ball};
+pub use link::{Link, LinkConditions, Loopback};
+pub use paddle::{PADDLE_WIDTH, Paddle, PaddleInput};
+pub use protocol::{
+    Datagram, MAX_DATAGRAM_INPUTS, MAX_MESSAGE_LEN, Message, PROTOCOL_VERSION, Snapshot,
+};
+pub use replay::{FORMAT_VERSION, Playback, Replay};
+pub use rng::Rng;
+pub use rollback::{Exchange, Rollback, RollbackSettings, RollbackStats};
+pub use score::{MatchRules, Score, Side};
+pub use state::{GameSettings, GameState, Inputs, Serve};
+pub use stats::{MatchStats, PlayerStats};
+pub use wire::DecodeError;
+
+/// Width of the arena every match is played in, in logical units.
+///
+/// The arena is the same size no matter how it is displayed, so a match plays out identically
+/// on every terminal.
+pub const ARENA_WIDTH: f64 = 160.0;
+
+/// Height of the arena, in logical units.
+pub const ARENA_HEIGHT: f64 = 90.0;
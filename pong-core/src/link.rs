@@ -0,0 +1,98 @@
+use std::{cell::RefCell, collections::VecDeque, io, rc::Rc};
+
+use crate::Rng;
+
+/// A way of sending datagrams to the other peer of a rollback match, which may lose, delay or
+/// reorder them.
+pub trait Link {
+    fn send(&mut self, datagram: &[u8]) -> io::Result<()>;
+
+    /// The next datagram that has arrived, if any. Never blocks.
+    fn receive(&mut self) -> io::Result<Option<Vec<u8>>>;
+}
+
+/// How badly a [`Loopback`] treats the datagrams sent over it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkConditions {
+    /// Seconds each datagram takes to arrive.
+    pub latency: f64,
+    /// Up to this many extra seconds, picked at random for each datagram, so some overtake
+    /// others.
+    pub jitter: f64,
+    /// Share of datagrams that never arrive, from 0 to 1.
+    pub loss: f64,
+}
+
+/// One end of an in-memory [`Link`], for trying rollback matches without a network.
+///
+/// Time only passes when [`Loopback::advance`] is called, so a run with the same seed loses and
+/// delays the same datagrams every time.
+#[derive(Debug, Clone)]
+pub struct Loopback {
+    shared: Rc<RefCell<Shared>>,
+    /// Which of the two ends this is, as an index into [`Shared::queues`].
+    end: usize,
+}
+
+#[derive(Debug)]
+struct Shared {
+    conditions: LinkConditions,
+    rng: Rng,
+    now: f64,
+    /// Datagrams on their way to each end, with the time they arrive.
+    queues: [VecDeque<(f64, Vec<u8>)>; 2],
+}
+
+impl Loopback {
+    /// Both ends of a new link.
+    pub fn pair(conditions: LinkConditions, seed: u64) -> (Self, Self) {
+        let shared = Rc::new(RefCell::new(Shared {
+            conditions,
+            rng: Rng::new(seed),
+            now: 0.0,
+            queues: Default::default(),
+        }));
+        let other = Self {
+            shared: Rc::clone(&shared),
+            end: 1,
+        };
+        (Self { shared, end: 0 }, other)
+    }
+
+    /// Lets `dt` seconds pass, for both ends.
+    pub fn advance(&self, dt: f64) {
+        self.shared.borrow_mut().now += dt;
+    }
+}
+
+impl Link for Loopback {
+    fn send(&mut self, datagram: &[u8]) -> io::Result<()> {
+        let shared = &mut *self.shared.borrow_mut();
+        let LinkConditions {
+            latency,
+            jitter,
+            loss,
+        } = shared.conditions;
+        if shared.rng.next_f64() < loss {
+            return Ok(());
+        }
+        let arrival = shared.now + latency + jitter * shared.rng.next_f64();
+        shared.queues[1 - self.end].push_back((arrival, datagram.to_vec()));
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let shared = &mut *self.shared.borrow_mut();
+        let now = shared.now;
+        let queue = &mut shared.queues[self.end];
+        let arrived = queue
+            .iter()
+            .enumerate()
+            .filter(|(_, (arrival, _))| *arrival <= now)
+            .min_by(|(_, a), (_, b)| a.0.total_cmp(&b.0))
+            .map(|(index, _)| index);
+        Ok(arrived
+            .and_then(|index| queue.remove(index))
+            .map(|(_, datagram)| datagram))
+    }
+}
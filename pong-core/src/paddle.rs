@@ -0,0 +1,76 @@
+use crate::{ARENA_HEIGHT, ARENA_WIDTH, BALL_SIZE, Side};
+
+/// Speed of a paddle, in arena units per second.
+pub const PADDLE_SPEED: f64 = 120.0;
+
+/// Width of a paddle, in arena units.
+pub const PADDLE_WIDTH: f64 = 2.0;
+
+/// Height of a normal sized paddle, in arena units.
+pub const PADDLE_HEIGHT: f64 = ARENA_HEIGHT / 5.0;
+
+/// Gap between a paddle and its edge of the arena, in arena units.
+const PADDLE_MARGIN: f64 = 2.0;
+
+/// What a player asks their paddle to do during one step.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PaddleInput {
+    #[default]
+    Idle,
+    /// Move at a fraction of [`PADDLE_SPEED`], from `-1.0` (full speed up) to `1.0` (full speed
+    /// down).
+    Move(f64),
+    /// Move the centre of the paddle toward this height, in arena units, at up to
+    /// [`PADDLE_SPEED`].
+    Target(f64),
+}
+
+/// A paddle, positioned by its top-left corner in arena units.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Paddle {
+    pub side: Side,
+    pub x: f64,
+    pub y: f64,
+    pub height: f64,
+}
+
+impl Paddle {
+    /// Places a paddle `height` arena units tall vertically centred on its side of the arena.
+    pub fn new(side: Side, height: f64) -> Self {
+        let height = height.clamp(BALL_SIZE, ARENA_HEIGHT);
+        let mut paddle = Self {
+            side,
+            x: 0.0,
+            y: (ARENA_HEIGHT - height) / 2.0,
+            height,
+        };
+        paddle.clamp();
+        paddle
+    }
+
+    pub fn center(&self) -> f64 {
+        self.y + self.height / 2.0
+    }
+
+    /// Moves the paddle as `input` asks for `dt` seconds.
+    pub fn update(&mut self, input: PaddleInput, dt: f64) {
+        match input {
+            PaddleInput::Idle => {}
+            PaddleInput::Move(speed) => self.y += speed.clamp(-1.0, 1.0) * PADDLE_SPEED * dt,
+            PaddleInput::Target(y) => {
+                let step = PADDLE_SPEED * dt;
+                self.y += (y - self.center()).clamp(-step, step);
+            }
+        }
+        self.clamp();
+    }
+
+    /// Keeps the paddle against its edge and inside the top and bottom of the arena.
+    fn clamp(&mut self) {
+        self.x = match self.side {
+            Side::Left => PADDLE_MARGIN,
+            Side::Right => ARENA_WIDTH - PADDLE_MARGIN - PADDLE_WIDTH,
+        };
+        self.y = self.y.clamp(0.0, ARENA_HEIGHT - self.height);
+    }
+}
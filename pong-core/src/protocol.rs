@@ -0,0 +1,493 @@
+use crate::{
+    GameSettings, GameState, PaddleInput,
+    wire::{DecodeError, Reader, write_input, write_name, write_settings, write_varint},
+};
+
+/// First bytes of a [`Message::Hello`] and of every [`Datagram`].
+const MAGIC: &[u8; 4] = b"PONG";
+
+/// Version of the network protocol; both ends must speak the same one.
+pub const PROTOCOL_VERSION: u8 = 3;
+
+/// Largest message either end accepts, in bytes, not counting its length prefix.
+pub const MAX_MESSAGE_LEN: usize = 1024;
+
+/// Most inputs a single [`Datagram::Inputs`] carries.
+pub const MAX_DATAGRAM_INPUTS: usize = 64;
+
+/// The host's view of a match, sent to the client and spectators many times a second.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub game: GameState,
+    /// Whether the host has paused the match.
+    pub paused: bool,
+    /// Seconds left before the match picks up again after a pause.
+    pub countdown: Option<f64>,
+    /// How many are watching the match.
+    pub spectators: u32,
+}
+
+/// Everything the host of a networked match says to the client and spectators, and they to it.
+///
+/// On the wire each message is a two byte little-endian length followed by that many bytes: a
+/// tag, then the message's fields.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// The first message a client sends. Its layout never changes, so a host can always tell a
+    /// client speaking another protocol version from garbage.
+    Hello {
+        version: u8,
+    },
+    /// The first message a spectator sends, laid out like [`Message::Hello`].
+    Watch {
+        version: u8,
+    },
+    /// The host accepted the client or spectator; the match starts with the next snapshot.
+    Welcome,
+    /// The host turned the client or spectator away, and why.
+    Reject(String),
+    /// The client's paddle input, sent every tick. Spectators send it too, always idle.
+    Input(PaddleInput),
+    Snapshot(Box<Snapshot>),
+    /// The sender is leaving the match.
+    Bye,
+}
+
+impl Message {
+    /// The hello of this protocol version.
+    pub fn hello() -> Self {
+        Self::Hello {
+            version: PROTOCOL_VERSION,
+        }
+    }
+
+    /// The spectator's hello of this protocol version.
+    pub fn watch() -> Self {
+        Self::Watch {
+            version: PROTOCOL_VERSION,
+        }
+    }
+
+    /// The message with its length prefix, ready to be written to the connection.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = vec![0, 0];
+        match self {
+            Self::Hello { version } => {
+                out.push(0);
+                out.extend_from_slice(MAGIC);
+                out.push(*version);
+            }
+            Self::Welcome => out.push(1),
+            Self::Reject(reason) => {
+                out.push(2);
+                write_name(&mut out, reason);
+            }
+            Self::Input(input) => {
+                out.push(3);
+                write_input(&mut out, *input);
+            }
+            Self::Snapshot(snapshot) => {
+                out.push(4);
+                out.push(u8::from(snapshot.paused));
+                match snapshot.countdown {
+                    Some(seconds) => {
+                        out.push(1);
+                        out.extend_from_slice(&seconds.to_le_bytes());
+                    }
+                    None => out.push(0),
+                }
+                write_varint(&mut out, snapshot.spectators.into());
+                snapshot.game.write(&mut out);
+            }
+            Self::Bye => out.push(5),
+            Self::Watch { version } => {
+                out.push(6);
+                out.extend_from_slice(MAGIC);
+                out.push(*version);
+            }
+        }
+        let len = (out.len() - 2) as u16;
+        out[..2].copy_from_slice(&len.to_le_bytes());
+        out
+    }
+
+    /// Reads the first message in `bytes`, returning it along with how many bytes it took up.
+    ///
+    /// Returns `Ok(None)` if the message hasn't fully arrived yet.
+    pub fn decode(bytes: &[u8]) -> Result<Option<(Self, usize)>, DecodeError> {
+        let Some(prefix) = bytes.get(..2) else {
+            return Ok(None);
+        };
+        let len = usize::from(u16::from_le_bytes([prefix[0], prefix[1]]));
+        if len == 0 || len > MAX_MESSAGE_LEN {
+            return Err(DecodeError::Corrupt);
+        }
+        let Some(body) = bytes.get(2..2 + len) else {
+            return Ok(None);
+        };
+
+        let mut reader = Reader::new(body);
+        let message = match reader.u8()? {
+            0 => {
+                if reader.take(MAGIC.len())? != MAGIC {
+                    return Err(DecodeError::BadMagic);
+                }
+                Self::Hello {
+                    version: reader.u8()?,
+                }
+            }
+            1 => Self::Welcome,
+            2 => Self::Reject(reader.name()?),
+            3 => Self::Input(reader.input()?),
+            4 => Self::Snapshot(Box::new(Snapshot {
+                paused: reader.bool()?,
+                countdown: if reader.bool()? {
+                    Some(reader.f64()?)
+                } else {
+                    None
+                },
+                spectators: u32::try_from(reader.varint()?).map_err(|_| DecodeError::Corrupt)?,
+                game: GameState::read(&mut reader)?,
+            })),
+            5 => Self::Bye,
+            6 => {
+                if reader.take(MAGIC.len())? != MAGIC {
+                    return Err(DecodeError::BadMagic);
+                }
+                Self::Watch {
+                    version: reader.u8()?,
+                }
+            }
+            _ => return Err(DecodeError::Corrupt),
+        };
+        if !reader.is_empty() {
+            return Err(DecodeError::Corrupt);
+        }
+        Ok(Some((message, 2 + len)))
+    }
+}
+
+/// Everything the two peers of a rollback match say to each other, one datagram at a time.
+///
+/// Datagrams can be lost, duplicated or reordered, so every one stands on its own: each starts
+/// with the same magic bytes, then a tag and the fields.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Datagram {
+    /// Sent by the peer joining a match until it hears back.
+    Hello { version: u8 },
+    /// Sent by the hosting peer until the match is under way: how the match is set up. The
+    /// joining peer plays the right paddle.
+    Start {
+        seed: u64,
+        settings: GameSettings,
+        tick_rate: u32,
+    },
+    /// The hosting peer turned the joining one away, and why.
+    Reject(String),
+    /// The sender's inputs from tick `first` on, along with the tick of the first input it is
+    /// still waiting for from the receiver.
+    Inputs {
+        ack: u64,
+        first: u64,
+        inputs: Vec<PaddleInput>,
+    },
+    /// The sender is leaving the match.
+    Bye,
+}
+
+impl Datagram {
+    /// The hello of this protocol version.
+    pub fn hello() -> Self {
+        Self::Hello {
+            version: PROTOCOL_VERSION,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        match self {
+            Self::Hello { version } => {
+                out.push(0);
+                out.push(*version);
+            }
+            Self::Start {
+                seed,
+                settings,
+                tick_rate,
+            } => {
+                out.push(1);
+                out.extend_from_slice(&seed.to_le_bytes());
+                write_settings(&mut out, settings);
+                out.extend_from_slice(&tick_rate.to_le_bytes());
+            }
+            Self::Reject(reason) => {
+                out.push(2);
+                write_name(&mut out, reason);
+            }
+            Self::Inputs { ack, first, inputs } => {
+                out.push(3);
+                write_varint(&mut out, *ack);
+                write_varint(&mut out, *first);
+                let inputs = &inputs[..inputs.len().min(MAX_DATAGRAM_INPUTS)];
+                write_varint(&mut out, inputs.len() as u64);
+                for &input in inputs {
+                    write_input(&mut out, input);
+                }
+            }
+            Self::Bye => out.push(4),
+        }
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        let datagram = match reader.u8()? {
+            0 => Self::Hello {
+                version: reader.u8()?,
+            },
+            1 => {
+                let seed = reader.u64()?;
+                let settings = reader.settings()?;
+                let tick_rate = reader.u32()?;
+                if tick_rate == 0 {
+                    return Err(DecodeError::Corrupt);
+                }
+                Self::Start {
+                    seed,
+                    settings,
+                    tick_rate,
+                }
+            }
+            2 => Self::Reject(reader.name()?),
+            3 => {
+                let ack = reader.varint()?;
+                let first = reader.varint()?;
+                let count = usize::try_from(reader.varint()?)
+                    .ok()
+                    .filter(|&count| count <= MAX_DATAGRAM_INPUTS)
+                    .ok_or(DecodeError::Corrupt)?;
+                let inputs = (0..count)
+                    .map(|_| reader.input())
+                    .collect::<Result<_, _>>()?;
+                Self::Inputs { ack, first, inputs }
+            }
+            4 => Self::Bye,
+            _ => return Err(DecodeError::Corrupt),
+        };
+        if !reader.is_empty() {
+            return Err(DecodeError::Corrupt);
+        }
+        Ok(datagram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ai, Difficulty, Inputs, MatchRules, Side};
+
+    /// A match some way in, with the ball in play and points scored.
+    fn game_in_progress() -> GameState {
+        let mut game = GameState::new(GameSettings::default(), 42);
+        let mut left = Ai::new(
+            Side::Left,
+            Difficulty::Hard.settings(),
+            game.side_rng(Side::Left),
+        );
+        let mut right = Ai::new(
+            Side::Right,
+            Difficulty::Easy.settings(),
+            game.side_rng(Side::Right),
+        );
+        let dt = 1.0 / 60.0;
+        for _ in 0..60 * 30 {
+            let inputs = Inputs {
+                left: left.input(&game, dt),
+                right: right.input(&game, dt),
+            };
+            game.step(inputs, dt);
+        }
+        game
+    }
+
+    fn messages() -> Vec<Message> {
+        vec![
+            Message::hello(),
+            Message::watch(),
+            Message::Welcome,
+            Message::Reject("the match is full".to_string()),
+            Message::Input(PaddleInput::Idle),
+            Message::Input(PaddleInput::Move(-1.0)),
+            Message::Input(PaddleInput::Target(12.5)),
+            Message::Snapshot(Box::new(Snapshot {
+                game: game_in_progress(),
+                paused: true,
+                countdown: Some(2.5),
+                spectators: 3,
+            })),
+            Message::Snapshot(Box::new(Snapshot {
+                game: GameState::new(GameSettings::default(), 1),
+                paused: false,
+                countdown: None,
+                spectators: 0,
+            })),
+            Message::Bye,
+        ]
+    }
+
+    fn datagrams() -> Vec<Datagram> {
+        vec![
+            Datagram::hello(),
+            Datagram::Start {
+                seed: u64::MAX,
+                settings: GameSettings {
+                    rules: MatchRules {
+                        target: 21,
+                        win_by_two: true,
+                    },
+                    ball_speed: 1.5,
+                    paddle_size: 0.75,
+                },
+                tick_rate: 120,
+            },
+            Datagram::Reject("the match is full".to_string()),
+            Datagram::Inputs {
+                ack: 1 << 40,
+                first: 300,
+                inputs: vec![
+                    PaddleInput::Idle,
+                    PaddleInput::Move(1.0),
+                    PaddleInput::Target(3.0),
+                ],
+            },
+            Datagram::Inputs {
+                ack: 0,
+                first: 0,
+                inputs: Vec::new(),
+            },
+            Datagram::Bye,
+        ]
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        for message in messages() {
+            let bytes = message.encode();
+            assert_eq!(Message::decode(&bytes), Ok(Some((message, bytes.len()))));
+        }
+    }
+
+    #[test]
+    fn messages_are_read_one_after_another() {
+        let bytes: Vec<u8> = messages().iter().flat_map(Message::encode).collect();
+        let mut decoded = Vec::new();
+        let mut start = 0;
+        while let Some((message, len)) = Message::decode(&bytes[start..]).unwrap() {
+            decoded.push(message);
+            start += len;
+        }
+        assert_eq!(decoded, messages());
+        assert_eq!(start, bytes.len());
+    }
+
+    #[test]
+    fn partial_messages_wait_for_the_rest() {
+        for message in messages() {
+            let bytes = message.encode();
+            for len in 0..bytes.len() {
+                assert_eq!(Message::decode(&bytes[..len]), Ok(None));
+            }
+        }
+    }
+
+    #[test]
+    fn snapshots_carry_the_whole_state() {
+        let game = game_in_progress();
+        let mut bytes = Vec::new();
+        game.write(&mut bytes);
+        let mut reader = Reader::new(&bytes);
+        let read = GameState::read(&mut reader).unwrap();
+        assert!(reader.is_empty());
+        assert_eq!(read, game);
+
+        // the copy plays on exactly like the original
+        let (mut original, mut copy) = (game, read);
+        let inputs = Inputs {
+            left: PaddleInput::Move(1.0),
+            right: PaddleInput::Target(40.0),
+        };
+        for _ in 0..600 {
+            original.step(inputs, 1.0 / 60.0);
+            copy.step(inputs, 1.0 / 60.0);
+        }
+        assert_eq!(copy, original);
+    }
+
+    #[test]
+    fn corrupt_messages_are_rejected() {
+        // a length of zero, or longer than any message may be
+        assert_eq!(Message::decode(&[0, 0, 1]), Err(DecodeError::Corrupt));
+        let too_long = (MAX_MESSAGE_LEN as u16 + 1).to_le_bytes();
+        assert_eq!(Message::decode(&too_long), Err(DecodeError::Corrupt));
+        // an unknown tag
+        assert_eq!(Message::decode(&[1, 0, 99]), Err(DecodeError::Corrupt));
+        // a hello without the magic bytes
+        assert_eq!(
+            Message::decode(&[6, 0, 0, b'P', b'I', b'N', b'G', 1]),
+            Err(DecodeError::BadMagic)
+        );
+        // a message with bytes left over
+        assert_eq!(Message::decode(&[2, 0, 1, 0]), Err(DecodeError::Corrupt));
+        // a message whose length cuts its fields short
+        let mut bytes = Message::Input(PaddleInput::Move(1.0)).encode();
+        bytes[0] -= 1;
+        bytes.pop();
+        assert_eq!(Message::decode(&bytes), Err(DecodeError::Truncated));
+    }
+
+    #[test]
+    fn datagrams_round_trip() {
+        for datagram in datagrams() {
+            assert_eq!(Datagram::decode(&datagram.encode()), Ok(datagram));
+        }
+    }
+
+    #[test]
+    fn truncated_datagrams_are_rejected() {
+        for datagram in datagrams() {
+            let bytes = datagram.encode();
+            for len in 0..bytes.len() {
+                assert!(
+                    Datagram::decode(&bytes[..len]).is_err(),
+                    "{datagram:?} cut at {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn corrupt_datagrams_are_rejected() {
+        assert_eq!(Datagram::decode(b"PING\x04"), Err(DecodeError::BadMagic));
+        assert_eq!(Datagram::decode(b"PONG\x63"), Err(DecodeError::Corrupt));
+        assert_eq!(Datagram::decode(b"PONG\x04\x00"), Err(DecodeError::Corrupt));
+
+        let mut start = Datagram::Start {
+            seed: 1,
+            settings: GameSettings::default(),
+            tick_rate: 60,
+        }
+        .encode();
+        let len = start.len();
+        start[len - 4..].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(Datagram::decode(&start), Err(DecodeError::Corrupt));
+
+        // more inputs than a datagram may carry
+        let mut inputs = b"PONG\x03\x00\x00".to_vec();
+        write_varint(&mut inputs, MAX_DATAGRAM_INPUTS as u64 + 1);
+        inputs.extend(std::iter::repeat_n(0, MAX_DATAGRAM_INPUTS + 1));
+        assert_eq!(Datagram::decode(&inputs), Err(DecodeError::Corrupt));
+    }
+}
@@ -0,0 +1,316 @@
+use crate::{
+    GameSettings, GameState, Inputs,
+    wire::{DecodeError, Reader, write_input, write_name, write_settings, write_varint},
+};
+
+/// First bytes of every replay file.
+const MAGIC: &[u8; 8] = b"PONGRPLY";
+
+/// Version of the replay format, bumped whenever it changes.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Most ticks a replay file may hold, so a corrupt one can't claim to need all of memory.
+const MAX_TICKS: usize = 1 << 22;
+
+/// Ticks between the snapshots a [`Playback`] keeps for seeking backwards.
+const CHECKPOINT_INTERVAL: usize = 300;
+
+/// Everything needed to play a match again: how it was set up and what both players did on
+/// every tick. The simulation is deterministic, so the rest follows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub settings: GameSettings,
+    /// Simulation steps per second the match was played at.
+    pub tick_rate: u32,
+    /// Who played each side, e.g. "Player 1" or "Computer (Hard)".
+    pub left: String,
+    pub right: String,
+    inputs: Vec<Inputs>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, settings: GameSettings, tick_rate: u32) -> Self {
+        Self {
+            seed,
+            settings,
+            tick_rate,
+            left: String::new(),
+            right: String::new(),
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Adds the inputs of the next tick.
+    pub fn record(&mut self, inputs: Inputs) {
+        self.inputs.push(inputs);
+    }
+
+    /// Number of ticks recorded.
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Length of one tick, in seconds.
+    pub fn dt(&self) -> f64 {
+        1.0 / f64::from(self.tick_rate)
+    }
+
+    /// Plays the whole match through and returns how it ended.
+    pub fn final_state(&self) -> GameState {
+        let mut game = GameState::new(self.settings, self.seed);
+        for &inputs in &self.inputs {
+            game.step(inputs, self.dt());
+        }
+        game
+    }
+
+    /// Writes the replay in its binary format.
+    ///
+    /// After a header with the setup, the inputs are stored as runs of identical ticks, since
+    /// players hold the same keys for many ticks in a row.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(FORMAT_VERSION);
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        write_settings(&mut out, &self.settings);
+        out.extend_from_slice(&self.tick_rate.to_le_bytes());
+        write_name(&mut out, &self.left);
+        write_name(&mut out, &self.right);
+
+        for run in self.inputs.chunk_by(|a, b| a == b) {
+            write_varint(&mut out, run.len() as u64);
+            write_input(&mut out, run[0].left);
+            write_input(&mut out, run[0].right);
+        }
+        out
+    }
+
+    /// Reads a replay written by [`Replay::encode`].
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        reader.header(MAGIC, FORMAT_VERSION)?;
+
+        let seed = reader.u64()?;
+        let settings = reader.settings()?;
+        let tick_rate = reader.u32()?;
+        if tick_rate == 0 {
+            return Err(DecodeError::Corrupt);
+        }
+        let mut replay = Self::new(seed, settings, tick_rate);
+        replay.left = reader.name()?;
+        replay.right = reader.name()?;
+
+        while !reader.is_empty() {
+            let count = reader.varint()?;
+            let inputs = Inputs {
+                left: reader.input()?,
+                right: reader.input()?,
+            };
+            let count = usize::try_from(count)
+                .ok()
+                .filter(|&count| {
+                    replay
+                        .len()
+                        .checked_add(count)
+                        .is_some_and(|len| len <= MAX_TICKS)
+                })
+                .ok_or(DecodeError::Corrupt)?;
+            replay.inputs.extend(std::iter::repeat_n(inputs, count));
+        }
+        Ok(replay)
+    }
+}
+
+/// Plays a [`Replay`] back one tick at a time, and can jump to any tick.
+#[derive(Debug, Clone)]
+pub struct Playback {
+    replay: Replay,
+    game: GameState,
+    /// Ticks played so far.
+    tick: usize,
+    /// The state every [`CHECKPOINT_INTERVAL`] ticks, as far as playback has got.
+    checkpoints: Vec<GameState>,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        let game = GameState::new(replay.settings, replay.seed);
+        Self {
+            replay,
+            checkpoints: vec![game.clone()],
+            game,
+            tick: 0,
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// The match as of the current tick.
+    pub fn game(&self) -> &GameState {
+        &self.game
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.replay.len()
+    }
+
+    /// Plays the next tick, if there is one.
+    pub fn step(&mut self) {
+        let Some(&inputs) = self.replay.inputs.get(self.tick) else {
+            return;
+        };
+        self.game.step(inputs, self.replay.dt());
+        self.tick += 1;
+        if self.tick == self.checkpoints.len() * CHECKPOINT_INTERVAL {
+            self.checkpoints.push(self.game.clone());
+        }
+    }
+
+    /// Moves to `tick`, clamped to the length of the replay.
+    ///
+    /// Going backwards restarts from the nearest checkpoint before `tick`, so it costs at most
+    /// [`CHECKPOINT_INTERVAL`] ticks of simulation.
+    pub fn seek(&mut self, tick: usize) {
+        let tick = tick.min(self.replay.len());
+        let checkpoint = (tick / CHECKPOINT_INTERVAL).min(self.checkpoints.len() - 1);
+        if tick < self.tick || checkpoint * CHECKPOINT_INTERVAL > self.tick {
+            self.game = self.checkpoints[checkpoint].clone();
+            self.tick = checkpoint * CHECKPOINT_INTERVAL;
+        }
+        while self.tick < tick {
+            self.step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ai, Difficulty, MatchRules, PaddleInput, Side};
+
+    /// A whole match between two computers, recorded.
+    fn recorded_match() -> Replay {
+        let settings = GameSettings {
+            rules: MatchRules {
+                target: 3,
+                win_by_two: true,
+            },
+            ball_speed: 1.25,
+            paddle_size: 0.8,
+        };
+        let mut replay = Replay::new(9, settings, 60);
+        replay.left = "Computer (Hard)".to_string();
+        replay.right = "Computer (Easy)".to_string();
+        let mut game = GameState::new(settings, replay.seed);
+        let mut left = Ai::new(
+            Side::Left,
+            Difficulty::Hard.settings(),
+            game.side_rng(Side::Left),
+        );
+        let mut right = Ai::new(
+            Side::Right,
+            Difficulty::Easy.settings(),
+            game.side_rng(Side::Right),
+        );
+        while game.winner().is_none() {
+            let inputs = Inputs {
+                left: left.input(&game, replay.dt()),
+                right: right.input(&game, replay.dt()),
+            };
+            replay.record(inputs);
+            game.step(inputs, replay.dt());
+        }
+        replay
+    }
+
+    #[test]
+    fn replays_round_trip() {
+        let replay = recorded_match();
+        let decoded = Replay::decode(&replay.encode()).unwrap();
+        assert_eq!(decoded, replay);
+        assert!(decoded.final_state().winner().is_some());
+    }
+
+    #[test]
+    fn held_inputs_are_stored_as_runs() {
+        let mut replay = Replay::new(1, GameSettings::default(), 60);
+        let inputs = Inputs {
+            left: PaddleInput::Move(-1.0),
+            right: PaddleInput::Idle,
+        };
+        for _ in 0..10_000 {
+            replay.record(inputs);
+        }
+        let bytes = replay.encode();
+        assert!(bytes.len() < 64);
+        assert_eq!(Replay::decode(&bytes), Ok(replay));
+    }
+
+    #[test]
+    fn truncated_replays_are_rejected() {
+        let bytes = recorded_match().encode();
+        // anything short of the header and names, or cut inside a run of inputs
+        let header = MAGIC.len() + 1 + 8 + 4 + 1 + 8 + 8 + 4;
+        for len in 0..header + 2 {
+            assert!(Replay::decode(&bytes[..len]).is_err(), "cut at {len}");
+        }
+        assert_eq!(
+            Replay::decode(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::Truncated)
+        );
+    }
+
+    #[test]
+    fn corrupt_replays_are_rejected() {
+        let bytes = recorded_match().encode();
+
+        let mut magic = bytes.clone();
+        magic[0] = b'X';
+        assert_eq!(Replay::decode(&magic), Err(DecodeError::BadMagic));
+
+        let mut version = bytes.clone();
+        version[MAGIC.len()] = FORMAT_VERSION + 1;
+        assert_eq!(
+            Replay::decode(&version),
+            Err(DecodeError::UnsupportedVersion {
+                found: FORMAT_VERSION + 1,
+                expected: FORMAT_VERSION,
+            })
+        );
+
+        // an input of a kind that doesn't exist
+        let mut input = Replay::new(1, GameSettings::default(), 60);
+        input.record(Inputs::default());
+        let mut input = input.encode();
+        *input.last_mut().unwrap() = 7;
+        assert_eq!(Replay::decode(&input), Err(DecodeError::Corrupt));
+
+        // a run longer than any replay may be
+        let mut run = Replay::new(1, GameSettings::default(), 60).encode();
+        write_varint(&mut run, MAX_TICKS as u64 + 1);
+        write_input(&mut run, PaddleInput::Idle);
+        write_input(&mut run, PaddleInput::Idle);
+        assert_eq!(Replay::decode(&run), Err(DecodeError::Corrupt));
+
+        // a run so long that adding it to the ticks so far would overflow
+        let mut huge = Replay::new(1, GameSettings::default(), 60);
+        huge.record(Inputs::default());
+        let mut huge = huge.encode();
+        write_varint(&mut huge, u64::MAX);
+        write_input(&mut huge, PaddleInput::Idle);
+        write_input(&mut huge, PaddleInput::Idle);
+        assert_eq!(Replay::decode(&huge), Err(DecodeError::Corrupt));
+    }
+}
@@ -0,0 +1,394 @@
+use std::{collections::VecDeque, io};
+
+use crate::{
+    Datagram, GameSettings, GameState, Inputs, Link, MAX_DATAGRAM_INPUTS, PaddleInput, Replay, Side,
+};
+
+/// Most ticks of the other peer's inputs kept ahead of the confirmed state, so a misbehaving
+/// peer can't fill up memory.
+const MAX_REMOTE_AHEAD: usize = 256;
+
+/// How a [`Rollback`] session trades responsiveness against corrections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RollbackSettings {
+    /// Ticks between reading a local input and it taking effect, which gives it that long to
+    /// reach the other peer before anything needs correcting.
+    pub input_delay: u32,
+    /// Most ticks the simulation may guess ahead of the other peer's inputs. Once that far
+    /// ahead it waits for them, and rolls back at most this many ticks.
+    pub window: u32,
+}
+
+impl Default for RollbackSettings {
+    fn default() -> Self {
+        Self {
+            input_delay: 2,
+            window: 8,
+        }
+    }
+}
+
+/// How much correcting a [`Rollback`] session has had to do.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RollbackStats {
+    /// Times a guess turned out wrong and the simulation was rolled back.
+    pub rollbacks: u64,
+    /// Ticks played again because of them.
+    pub resimulated: u64,
+    /// Ticks the session waited because the other peer's inputs were too far behind.
+    pub stalls: u64,
+}
+
+/// What came over the link during a [`Rollback::exchange`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Exchange {
+    /// Whether any datagram arrived from the other peer at all.
+    pub heard: bool,
+    /// Whether the other peer said it is leaving.
+    pub bye: bool,
+}
+
+/// One peer's view of a match played peer to peer with input delay and rollback.
+///
+/// Both peers run the whole simulation. Local inputs take effect `input_delay` ticks after they
+/// are read. A remote input that hasn't arrived yet is guessed to be the same as the last one
+/// that did; when the real one turns out different, the simulation goes back to the last state
+/// both peers' inputs are known for and plays the ticks since then again.
+#[derive(Debug, Clone)]
+pub struct Rollback {
+    settings: RollbackSettings,
+    local_side: Side,
+    dt: f64,
+    /// The state after every tick both peers' inputs are known for.
+    confirmed: GameState,
+    confirmed_tick: u64,
+    /// The state shown to the player, guessed ahead of `confirmed`.
+    game: GameState,
+    tick: u64,
+    /// Local inputs from `confirmed_tick` on, including the delayed ones not played yet.
+    local: VecDeque<PaddleInput>,
+    /// Remote inputs that have arrived, from `confirmed_tick` on.
+    remote: VecDeque<PaddleInput>,
+    /// The remote inputs `game` was played with, from `confirmed_tick` up to `tick`.
+    guesses: VecDeque<PaddleInput>,
+    /// The newest remote input, which is the guess for every tick after it.
+    latest_remote: PaddleInput,
+    /// Whether a guess has turned out wrong since `game` was last played forward.
+    mispredicted: bool,
+    /// Local inputs the other peer hasn't acknowledged, from tick `unacked_from` on.
+    unacked: VecDeque<PaddleInput>,
+    unacked_from: u64,
+    /// Both peers' inputs for every confirmed tick.
+    replay: Replay,
+    stats: RollbackStats,
+}
+
+impl Rollback {
+    /// A session for the peer playing `local_side`, at the start of a match both peers set up
+    /// the same way.
+    pub fn new(
+        game: GameSettings,
+        seed: u64,
+        tick_rate: u32,
+        local_side: Side,
+        settings: RollbackSettings,
+    ) -> Self {
+        let state = GameState::new(game, seed);
+        let delay = std::iter::repeat_n(PaddleInput::Idle, settings.input_delay as usize);
+        Self {
+            settings,
+            local_side,
+            dt: 1.0 / f64::from(tick_rate),
+            confirmed: state.clone(),
+            confirmed_tick: 0,
+            game: state,
+            tick: 0,
+            local: delay.clone().collect(),
+            remote: VecDeque::new(),
+            guesses: VecDeque::new(),
+            latest_remote: PaddleInput::Idle,
+            mispredicted: false,
+            unacked: delay.collect(),
+            unacked_from: 0,
+            replay: Replay::new(seed, game, tick_rate),
+            stats: RollbackStats::default(),
+        }
+    }
+
+    /// The match as the player should see it, including guessed ticks.
+    pub fn game(&self) -> &GameState {
+        &self.game
+    }
+
+    /// The match as far as both peers' inputs are known, which can no longer change.
+    pub fn confirmed(&self) -> &GameState {
+        &self.confirmed
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn confirmed_tick(&self) -> u64 {
+        self.confirmed_tick
+    }
+
+    /// How many ticks of the other peer's inputs have arrived.
+    pub fn remote_tick(&self) -> u64 {
+        self.confirmed_tick + self.remote.len() as u64
+    }
+
+    pub fn local_side(&self) -> Side {
+        self.local_side
+    }
+
+    pub fn settings(&self) -> RollbackSettings {
+        self.settings
+    }
+
+    pub fn stats(&self) -> RollbackStats {
+        self.stats
+    }
+
+    /// Every confirmed tick so far, ready to be saved.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Length of one tick, in seconds.
+    pub fn dt(&self) -> f64 {
+        self.dt
+    }
+
+    /// Plays the next tick with the local player's `input`, which takes effect after the input
+    /// delay.
+    ///
+    /// Returns `false` without doing anything if the other peer is too far behind.
+    pub fn advance(&mut self, input: PaddleInput) -> bool {
+        self.confirm();
+        if self.mispredicted {
+            self.resimulate();
+        }
+        if self.tick - self.confirmed_tick >= u64::from(self.settings.window) {
+            self.stats.stalls += 1;
+            return false;
+        }
+
+        self.local.push_back(input);
+        self.unacked.push_back(input);
+        let index = (self.tick - self.confirmed_tick) as usize;
+        let guess = self.remote_or_guess(index);
+        self.guesses.push_back(guess);
+        self.game
+            .step(self.inputs(self.local[index], guess), self.dt);
+        self.tick += 1;
+        true
+    }
+
+    /// Takes in inputs the other peer sent, from tick `first` on, and the tick of the first
+    /// local input it is still waiting for.
+    pub fn receive_inputs(&mut self, ack: u64, first: u64, inputs: &[PaddleInput]) {
+        let acked = ack.saturating_sub(self.unacked_from);
+        let acked = (acked as usize).min(self.unacked.len());
+        self.unacked.drain(..acked);
+        self.unacked_from += acked as u64;
+
+        let Some(skip) = self.remote_tick().checked_sub(first) else {
+            // a gap; what's missing will come again
+            return;
+        };
+        for &input in inputs.iter().skip(skip as usize) {
+            if self.remote.len() >= MAX_REMOTE_AHEAD {
+                break;
+            }
+            let index = self.remote.len();
+            if self.guesses.get(index).is_some_and(|&guess| guess != input) {
+                self.mispredicted = true;
+            }
+            self.remote.push_back(input);
+            self.latest_remote = input;
+        }
+    }
+
+    /// The datagram telling the other peer what it doesn't know yet: our unacknowledged inputs
+    /// and which of its inputs we are waiting for.
+    pub fn inputs_datagram(&self) -> Datagram {
+        Datagram::Inputs {
+            ack: self.remote_tick(),
+            first: self.unacked_from,
+            inputs: self
+                .unacked
+                .iter()
+                .take(MAX_DATAGRAM_INPUTS)
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Sends our inputs over `link` and takes in everything the other peer sent.
+    ///
+    /// Datagrams that can't be read are dropped, like any other lost datagram.
+    pub fn exchange(&mut self, link: &mut impl Link) -> io::Result<Exchange> {
+        link.send(&self.inputs_datagram().encode())?;
+        let mut exchange = Exchange::default();
+        while let Some(bytes) = link.receive()? {
+            match Datagram::decode(&bytes) {
+                Ok(Datagram::Inputs { ack, first, inputs }) => {
+                    exchange.heard = true;
+                    self.receive_inputs(ack, first, &inputs);
+                }
+                Ok(Datagram::Bye) => exchange.bye = true,
+                // leftovers from setting up the match
+                Ok(_) => exchange.heard = true,
+                Err(_) => {}
+            }
+        }
+        Ok(exchange)
+    }
+
+    /// Moves the confirmed state forward over every played tick both inputs are known for, up
+    /// to the end of the match, so the replay ends there too.
+    fn confirm(&mut self) {
+        while self.confirmed_tick < self.tick && self.confirmed.winner().is_none() {
+            let Some(remote) = self.remote.pop_front() else {
+                break;
+            };
+            let local = self.local.pop_front().unwrap_or_default();
+            self.guesses.pop_front();
+            let inputs = self.inputs(local, remote);
+            self.confirmed.step(inputs, self.dt);
+            self.replay.record(inputs);
+            self.confirmed_tick += 1;
+        }
+    }
+
+    /// Plays every unconfirmed tick again from the confirmed state, with the remote inputs
+    /// known by now.
+    fn resimulate(&mut self) {
+        self.game = self.confirmed.clone();
+        self.guesses.clear();
+        let ticks = (self.tick - self.confirmed_tick) as usize;
+        for index in 0..ticks {
+            let guess = self.remote_or_guess(index);
+            self.guesses.push_back(guess);
+            self.game
+                .step(self.inputs(self.local[index], guess), self.dt);
+        }
+        self.mispredicted = false;
+        self.stats.rollbacks += 1;
+        self.stats.resimulated += ticks as u64;
+    }
+
+    /// The remote input for the tick `index` ticks after the confirmed one, or the guess for it.
+    fn remote_or_guess(&self, index: usize) -> PaddleInput {
+        self.remote
+            .get(index)
+            .copied()
+            .unwrap_or(self.latest_remote)
+    }
+
+    fn inputs(&self, local: PaddleInput, remote: PaddleInput) -> Inputs {
+        match self.local_side {
+            Side::Left => Inputs {
+                left: local,
+                right: remote,
+            },
+            Side::Right => Inputs {
+                left: remote,
+                right: local,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ai, Difficulty, LinkConditions, Loopback, MatchRules};
+
+    const TICK_RATE: u32 = 60;
+
+    /// Plays a short match between two computers, each on its own peer, over a loopback link
+    /// with `conditions`, and returns both sessions once both have confirmed a winner.
+    fn play(conditions: LinkConditions, settings: RollbackSettings, seed: u64) -> [Rollback; 2] {
+        let game = GameSettings {
+            rules: MatchRules {
+                target: 3,
+                win_by_two: false,
+            },
+            ..GameSettings::default()
+        };
+        let dt = 1.0 / f64::from(TICK_RATE);
+        let start = GameState::new(game, seed);
+        let (left, right) = Loopback::pair(conditions, seed);
+        let mut peers = [(Side::Left, left), (Side::Right, right)].map(|(side, link)| {
+            let session = Rollback::new(game, seed, TICK_RATE, side, settings);
+            let ai = Ai::new(side, Difficulty::Normal.settings(), start.side_rng(side));
+            (session, ai, link)
+        });
+
+        for _ in 0..TICK_RATE * 60 * 20 {
+            if peers
+                .iter()
+                .all(|(session, ..)| session.confirmed().winner().is_some())
+            {
+                return peers.map(|(session, ..)| session);
+            }
+            peers[0].2.advance(dt);
+            for (session, ai, link) in &mut peers {
+                session.exchange(link).unwrap();
+                let input = ai.input(session.game(), dt);
+                session.advance(input);
+            }
+        }
+        panic!("the match never ended");
+    }
+
+    fn assert_agree([left, right]: &[Rollback; 2]) {
+        assert_eq!(left.confirmed(), right.confirmed());
+        assert_eq!(left.replay(), right.replay());
+        for session in [left, right] {
+            assert_eq!(session.replay().final_state(), *session.confirmed());
+        }
+    }
+
+    #[test]
+    fn peers_agree_over_a_bad_link() {
+        let conditions = LinkConditions {
+            latency: 0.08,
+            jitter: 0.04,
+            loss: 0.2,
+        };
+        let peers = play(conditions, RollbackSettings::default(), 7);
+        assert_agree(&peers);
+        assert!(peers.iter().all(|session| session.stats().rollbacks > 0));
+    }
+
+    #[test]
+    fn peers_agree_when_waiting_on_a_small_window() {
+        let conditions = LinkConditions {
+            latency: 0.15,
+            jitter: 0.05,
+            loss: 0.3,
+        };
+        let settings = RollbackSettings {
+            input_delay: 1,
+            window: 3,
+        };
+        let peers = play(conditions, settings, 11);
+        assert_agree(&peers);
+        assert!(peers.iter().all(|session| session.stats().stalls > 0));
+    }
+
+    #[test]
+    fn perfect_link_never_rolls_back() {
+        let conditions = LinkConditions {
+            latency: 0.0,
+            jitter: 0.0,
+            loss: 0.0,
+        };
+        let peers = play(conditions, RollbackSettings::default(), 3);
+        assert_agree(&peers);
+        assert!(peers.iter().all(|session| session.stats().rollbacks == 0));
+    }
+}
@@ -0,0 +1,108 @@
+/// Which half of the field something belongs to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    #[default]
+    Left,
+    Right,
+}
+
+impl Side {
+    pub fn opponent(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+/// Points scored by each side in the current match.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    pub left: u32,
+    pub right: u32,
+}
+
+impl Score {
+    pub fn award(&mut self, side: Side) {
+        match side {
+            Side::Left => self.left += 1,
+            Side::Right => self.right += 1,
+        }
+    }
+}
+
+/// When a match is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchRules {
+    /// Points needed to win the match.
+    pub target: u32,
+    /// Whether the winner must also lead by at least two points.
+    pub win_by_two: bool,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            target: 11,
+            win_by_two: false,
+        }
+    }
+}
+
+impl MatchRules {
+    /// The side that has won the match with `score`, if any.
+    pub fn winner(&self, score: Score) -> Option<Side> {
+        let (leader, high, low) = if score.left >= score.right {
+            (Side::Left, score.left, score.right)
+        } else {
+            (Side::Right, score.right, score.left)
+        };
+        let margin = if self.win_by_two { 2 } else { 1 };
+        (high >= self.target && high - low >= margin).then_some(leader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(left: u32, right: u32) -> Score {
+        Score { left, right }
+    }
+
+    #[test]
+    fn first_to_the_target_wins() {
+        let rules = MatchRules {
+            target: 11,
+            win_by_two: false,
+        };
+        assert_eq!(rules.winner(score(0, 0)), None);
+        assert_eq!(rules.winner(score(10, 10)), None);
+        assert_eq!(rules.winner(score(11, 10)), Some(Side::Left));
+        assert_eq!(rules.winner(score(3, 11)), Some(Side::Right));
+    }
+
+    #[test]
+    fn win_by_two_plays_on_past_deuce() {
+        let rules = MatchRules {
+            target: 11,
+            win_by_two: true,
+        };
+        assert_eq!(rules.winner(score(10, 10)), None);
+        assert_eq!(rules.winner(score(11, 10)), None);
+        assert_eq!(rules.winner(score(11, 11)), None);
+        assert_eq!(rules.winner(score(14, 15)), None);
+        assert_eq!(rules.winner(score(14, 16)), Some(Side::Right));
+        assert_eq!(rules.winner(score(11, 9)), Some(Side::Left));
+    }
+
+    #[test]
+    fn a_two_point_lead_short_of_the_target_does_not_win() {
+        let rules = MatchRules {
+            target: 5,
+            win_by_two: true,
+        };
+        assert_eq!(rules.winner(score(4, 0)), None);
+        assert_eq!(rules.winner(score(5, 0)), Some(Side::Left));
+    }
+}
@@ -0,0 +1,470 @@
+use crate::{
+    Ball, MatchRules, MatchStats, Paddle, PaddleInput, PlayerStats, Rng, Score, Side,
+    ball::{BALL_SPEED, MAX_BALL_SPEED, MAX_SERVE_ANGLE, MIN_SERVE_ANGLE},
+    paddle::PADDLE_HEIGHT,
+    wire::{DecodeError, Reader},
+};
+
+/// How long the ball waits in the middle before being served, in seconds.
+pub const SERVE_DELAY: f64 = 1.0;
+
+/// The inputs of both players for one step.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Inputs {
+    pub left: PaddleInput,
+    pub right: PaddleInput,
+}
+
+/// How a match is played.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameSettings {
+    pub rules: MatchRules,
+    /// Ball speed relative to normal, e.g. `1.5` for half as fast again.
+    pub ball_speed: f64,
+    /// Paddle height relative to normal.
+    pub paddle_size: f64,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            rules: MatchRules::default(),
+            ball_speed: 1.0,
+            paddle_size: 1.0,
+        }
+    }
+}
+
+/// A pending serve: the ball waits in the middle until `delay` runs out.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Serve {
+    pub toward: Side,
+    pub delay: f64,
+}
+
+/// A whole match: ball, paddles and score.
+///
+/// Everything random in a match comes from its seed, so the same seed and inputs always play
+/// out the same way.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GameState {
+    ball: Ball,
+    left: Paddle,
+    right: Paddle,
+    settings: GameSettings,
+    score: Score,
+    serve: Option<Serve>,
+    winner: Option<Side>,
+    stats: MatchStats,
+    seed: u64,
+    rng: Rng,
+}
+
+impl GameState {
+    /// Starts a match, with the first serve going to the right.
+    pub fn new(settings: GameSettings, seed: u64) -> Self {
+        let height = PADDLE_HEIGHT * settings.paddle_size;
+        let mut state = Self {
+            left: Paddle::new(Side::Left, height),
+            right: Paddle::new(Side::Right, height),
+            settings,
+            seed,
+            rng: Rng::new(seed),
+            ..Self::default()
+        };
+        state.queue_serve(Side::Right);
+        state
+    }
+
+    /// Advances the match by `dt` seconds with the players' `inputs`.
+    ///
+    /// Does nothing once the match has a winner.
+    pub fn step(&mut self, inputs: Inputs, dt: f64) {
+        if self.winner.is_some() {
+            return;
+        }
+
+        self.stats.elapse(dt);
+        self.left.update(inputs.left, dt);
+        self.right.update(inputs.right, dt);
+
+        if let Some(serve) = &mut self.serve {
+            serve.delay -= dt;
+            if serve.delay > 0.0 {
+                return;
+            }
+            let angle = self.rng.range(MIN_SERVE_ANGLE, MAX_SERVE_ANGLE);
+            let angle = if self.rng.next_f64() < 0.5 {
+                -angle
+            } else {
+                angle
+            };
+            self.ball = Ball::serve(serve.toward, self.serve_speed(), angle);
+            self.stats.ball_speed(self.ball.speed());
+            self.serve = None;
+        }
+
+        let previous_x = self.ball.x;
+        self.ball.update(dt);
+        let max_speed = MAX_BALL_SPEED * self.settings.ball_speed;
+        for side in [Side::Left, Side::Right] {
+            let paddle = match side {
+                Side::Left => &self.left,
+                Side::Right => &self.right,
+            };
+            if self.ball.collide(paddle, previous_x, max_speed) {
+                self.stats.hit(side);
+                self.stats.ball_speed(self.ball.speed());
+            }
+        }
+        if let Some(conceded) = self.ball.out_side() {
+            self.point(conceded.opponent());
+        }
+    }
+
+    pub fn ball(&self) -> &Ball {
+        &self.ball
+    }
+
+    pub fn paddle(&self, side: Side) -> &Paddle {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+
+    pub fn settings(&self) -> GameSettings {
+        self.settings
+    }
+
+    pub fn score(&self) -> Score {
+        self.score
+    }
+
+    /// The serve the ball is waiting for, if it is not in play.
+    pub fn serve(&self) -> Option<Serve> {
+        self.serve
+    }
+
+    /// The side that won the match, once it is over.
+    pub fn winner(&self) -> Option<Side> {
+        self.winner
+    }
+
+    pub fn stats(&self) -> MatchStats {
+        self.stats
+    }
+
+    /// The seed the match was started with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// A generator for randomness outside the state, such as the computer player on `side`.
+    ///
+    /// It follows from the seed like everything else, but drawing from it leaves the match's
+    /// own numbers alone, so a replay doesn't need to know who was playing.
+    pub fn side_rng(&self, side: Side) -> Rng {
+        let stream = match side {
+            Side::Left => 1,
+            Side::Right => 2,
+        };
+        Rng::new(self.seed ^ stream).fork()
+    }
+
+    fn serve_speed(&self) -> f64 {
+        BALL_SPEED * self.settings.ball_speed
+    }
+
+    /// Writes the whole state, so that [`GameState::read`] gives back an identical one.
+    pub(crate) fn write(&self, out: &mut Vec<u8>) {
+        let mut f64s = |values: &[f64]| {
+            for value in values {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        };
+        let ball = &self.ball;
+        f64s(&[ball.x, ball.y, ball.vx, ball.vy]);
+        f64s(&[self.left.x, self.left.y, self.left.height]);
+        f64s(&[self.right.x, self.right.y, self.right.height]);
+        f64s(&[self.settings.ball_speed, self.settings.paddle_size]);
+        f64s(&[self.stats.duration]);
+        out.extend_from_slice(&self.settings.rules.target.to_le_bytes());
+        out.push(u8::from(self.settings.rules.win_by_two));
+        out.extend_from_slice(&self.score.left.to_le_bytes());
+        out.extend_from_slice(&self.score.right.to_le_bytes());
+        match self.serve {
+            None => out.push(0),
+            Some(serve) => {
+                out.push(1 + side_byte(serve.toward));
+                out.extend_from_slice(&serve.delay.to_le_bytes());
+            }
+        }
+        out.push(self.winner.map_or(0, |side| 1 + side_byte(side)));
+        out.extend_from_slice(&self.stats.longest_rally.to_le_bytes());
+        out.extend_from_slice(&self.stats.rally.to_le_bytes());
+        out.extend_from_slice(&self.stats.fastest_ball.to_le_bytes());
+        for player in [self.stats.left, self.stats.right] {
+            out.extend_from_slice(&player.hits.to_le_bytes());
+            out.extend_from_slice(&player.misses.to_le_bytes());
+        }
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.extend_from_slice(&self.rng.state().to_le_bytes());
+    }
+
+    /// Reads a state written by [`GameState::write`].
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, DecodeError> {
+        let ball = Ball {
+            x: reader.f64()?,
+            y: reader.f64()?,
+            vx: reader.f64()?,
+            vy: reader.f64()?,
+        };
+        let mut paddle = |side| -> Result<Paddle, DecodeError> {
+            Ok(Paddle {
+                side,
+                x: reader.f64()?,
+                y: reader.f64()?,
+                height: reader.f64()?,
+            })
+        };
+        let left = paddle(Side::Left)?;
+        let right = paddle(Side::Right)?;
+        let ball_speed = reader.f64()?;
+        let paddle_size = reader.f64()?;
+        let duration = reader.f64()?;
+        let rules = MatchRules {
+            target: reader.u32()?,
+            win_by_two: reader.bool()?,
+        };
+        let score = Score {
+            left: reader.u32()?,
+            right: reader.u32()?,
+        };
+        let serve = match reader.u8()? {
+            0 => None,
+            tag @ 1..=2 => Some(Serve {
+                toward: byte_side(tag - 1),
+                delay: reader.f64()?,
+            }),
+            _ => return Err(DecodeError::Corrupt),
+        };
+        let winner = match reader.u8()? {
+            0 => None,
+            tag @ 1..=2 => Some(byte_side(tag - 1)),
+            _ => return Err(DecodeError::Corrupt),
+        };
+        let longest_rally = reader.u32()?;
+        let rally = reader.u32()?;
+        let fastest_ball = reader.f64()?;
+        let mut player = || -> Result<PlayerStats, DecodeError> {
+            Ok(PlayerStats {
+                hits: reader.u32()?,
+                misses: reader.u32()?,
+            })
+        };
+        let stats = MatchStats {
+            duration,
+            longest_rally,
+            fastest_ball,
+            left: player()?,
+            right: player()?,
+            rally,
+        };
+        Ok(Self {
+            ball,
+            left,
+            right,
+            settings: GameSettings {
+                rules,
+                ball_speed,
+                paddle_size,
+            },
+            score,
+            serve,
+            winner,
+            stats,
+            seed: reader.u64()?,
+            rng: Rng::new(reader.u64()?),
+        })
+    }
+
+    /// Awards a point to `side`, then either ends the match or serves toward the other side.
+    fn point(&mut self, side: Side) {
+        self.score.award(side);
+        self.stats.miss(side.opponent());
+        if let Some(winner) = self.settings.rules.winner(self.score) {
+            self.winner = Some(winner);
+            return;
+        }
+        self.queue_serve(side.opponent());
+    }
+
+    /// Parks the ball in the middle and serves it toward `side` after [`SERVE_DELAY`].
+    fn queue_serve(&mut self, side: Side) {
+        self.ball = Ball::serve(side, self.serve_speed(), 0.0);
+        self.serve = Some(Serve {
+            toward: side,
+            delay: SERVE_DELAY,
+        });
+    }
+}
+
+fn side_byte(side: Side) -> u8 {
+    match side {
+        Side::Left => 0,
+        Side::Right => 1,
+    }
+}
+
+fn byte_side(byte: u8) -> Side {
+    if byte == 0 { Side::Left } else { Side::Right }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ARENA_WIDTH, Ai, BALL_SIZE, Difficulty};
+
+    fn rules(target: u32, win_by_two: bool) -> GameSettings {
+        GameSettings {
+            rules: MatchRules { target, win_by_two },
+            ..GameSettings::default()
+        }
+    }
+
+    /// Plays a whole match between two computers with no terminal, giving up after an hour.
+    fn simulate(seed: u64) -> GameState {
+        let mut game = GameState::new(rules(5, true), seed);
+        let mut left = Ai::new(
+            Side::Left,
+            Difficulty::Hard.settings(),
+            game.side_rng(Side::Left),
+        );
+        let mut right = Ai::new(
+            Side::Right,
+            Difficulty::Normal.settings(),
+            game.side_rng(Side::Right),
+        );
+        let dt = 1.0 / 60.0;
+        for _ in 0..60 * 60 * 60 {
+            if game.winner().is_some() {
+                break;
+            }
+            let inputs = Inputs {
+                left: left.input(&game, dt),
+                right: right.input(&game, dt),
+            };
+            game.step(inputs, dt);
+        }
+        game
+    }
+
+    /// Puts the ball in play just short of `side`'s goal, above the paddle and heading in, and
+    /// steps until it gets past.
+    fn concede(game: &mut GameState, side: Side) {
+        let (x, vx) = match side {
+            Side::Left => (0.5, -BALL_SPEED),
+            Side::Right => (ARENA_WIDTH - BALL_SIZE - 0.5, BALL_SPEED),
+        };
+        game.serve = None;
+        game.ball = Ball {
+            x,
+            y: 0.0,
+            vx,
+            vy: 0.0,
+        };
+        game.step(Inputs::default(), 0.1);
+    }
+
+    #[test]
+    fn the_first_serve_waits_then_goes_right() {
+        let mut game = GameState::new(GameSettings::default(), 1);
+        let waiting = game.ball;
+        assert_eq!(
+            game.serve(),
+            Some(Serve {
+                toward: Side::Right,
+                delay: SERVE_DELAY,
+            })
+        );
+
+        game.step(Inputs::default(), SERVE_DELAY / 2.0);
+        assert_eq!(game.ball, waiting);
+        assert!(game.serve().is_some());
+
+        game.step(Inputs::default(), SERVE_DELAY / 2.0 + 0.1);
+        assert_eq!(game.serve(), None);
+        assert!(game.ball.vx > 0.0);
+    }
+
+    #[test]
+    fn points_are_served_toward_the_player_who_lost_them() {
+        let mut game = GameState::new(GameSettings::default(), 1);
+
+        concede(&mut game, Side::Left);
+        assert_eq!(game.score(), Score { left: 0, right: 1 });
+        assert_eq!(game.serve().map(|serve| serve.toward), Some(Side::Left));
+        assert_eq!(game.stats().left.misses, 1);
+        game.step(Inputs::default(), SERVE_DELAY + 0.1);
+        assert!(game.ball.vx < 0.0);
+
+        concede(&mut game, Side::Right);
+        assert_eq!(game.score(), Score { left: 1, right: 1 });
+        assert_eq!(game.serve().map(|serve| serve.toward), Some(Side::Right));
+        assert_eq!(game.stats().right.misses, 1);
+        game.step(Inputs::default(), SERVE_DELAY + 0.1);
+        assert!(game.ball.vx > 0.0);
+    }
+
+    #[test]
+    fn the_match_stops_once_it_has_a_winner() {
+        let mut game = GameState::new(rules(2, false), 1);
+        concede(&mut game, Side::Left);
+        assert_eq!(game.winner(), None);
+        concede(&mut game, Side::Left);
+        assert_eq!(game.winner(), Some(Side::Right));
+        assert_eq!(game.serve(), None);
+
+        let over = game.clone();
+        game.step(
+            Inputs {
+                left: PaddleInput::Move(1.0),
+                right: PaddleInput::Move(-1.0),
+            },
+            1.0,
+        );
+        assert_eq!(game, over);
+    }
+
+    #[test]
+    fn win_by_two_plays_on_past_deuce() {
+        let mut game = GameState::new(rules(2, true), 1);
+        for side in [Side::Left, Side::Right, Side::Left] {
+            concede(&mut game, side);
+        }
+        assert_eq!(game.score(), Score { left: 1, right: 2 });
+        assert_eq!(game.winner(), None);
+        assert_eq!(game.serve().map(|serve| serve.toward), Some(Side::Left));
+
+        concede(&mut game, Side::Left);
+        assert_eq!(game.winner(), Some(Side::Right));
+    }
+
+    #[test]
+    fn seeded_matches_play_out_the_same_every_time() {
+        let game = simulate(7);
+        let winner = game.winner().expect("the match should finish");
+        let score = game.score();
+        assert!(score.left.max(score.right) >= 5);
+        assert!(score.left.abs_diff(score.right) >= 2);
+        assert_eq!(
+            game.stats().player(winner.opponent()).misses,
+            score.left.max(score.right)
+        );
+        assert!(game.stats().hits() > 0);
+
+        assert_eq!(simulate(7), game);
+        assert_ne!(simulate(8), game);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::Side;
+
+/// Figures about a match, kept up to date as it is played.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MatchStats {
+    /// Seconds played, waits for serves included.
+    pub duration: f64,
+    /// Most paddle hits in a single point.
+    pub longest_rally: u32,
+    /// Fastest the ball has travelled, in arena units per second.
+    pub fastest_ball: f64,
+    pub left: PlayerStats,
+    pub right: PlayerStats,
+    /// Paddle hits so far in the current point.
+    pub(crate) rally: u32,
+}
+
+/// How one player has done at returning the ball.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerStats {
+    /// Balls sent back.
+    pub hits: u32,
+    /// Balls let past, each a point for the other player.
+    pub misses: u32,
+}
+
+impl PlayerStats {
+    /// Share of the balls that reached this player that were sent back, from 0 to 1, or
+    /// `None` before any did.
+    pub fn accuracy(&self) -> Option<f64> {
+        let chances = self.hits + self.misses;
+        (chances > 0).then(|| f64::from(self.hits) / f64::from(chances))
+    }
+}
+
+impl MatchStats {
+    pub fn player(&self, side: Side) -> PlayerStats {
+        match side {
+            Side::Left => self.left,
+            Side::Right => self.right,
+        }
+    }
+
+    /// Paddle hits by both players.
+    pub fn hits(&self) -> u32 {
+        self.left.hits + self.right.hits
+    }
+
+    pub(crate) fn elapse(&mut self, dt: f64) {
+        self.duration += dt;
+    }
+
+    /// Notes the ball's speed, whenever it changes.
+    pub(crate) fn ball_speed(&mut self, speed: f64) {
+        self.fastest_ball = self.fastest_ball.max(speed);
+    }
+
+    pub(crate) fn hit(&mut self, side: Side) {
+        self.player_mut(side).hits += 1;
+        self.rally += 1;
+        self.longest_rally = self.longest_rally.max(self.rally);
+    }
+
+    /// Ends the point, with the ball getting past `side`.
+    pub(crate) fn miss(&mut self, side: Side) {
+        self.player_mut(side).misses += 1;
+        self.rally = 0;
+    }
+
+    fn player_mut(&mut self, side: Side) -> &mut PlayerStats {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+}
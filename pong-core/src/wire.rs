@@ -0,0 +1,178 @@
+//! Little-endian building blocks shared by the replay format and the network protocol.
+
+use std::fmt;
+
+use crate::{GameSettings, MatchRules, PaddleInput};
+
+/// Why a replay or network message could not be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data doesn't start the way a replay or message does.
+    BadMagic,
+    UnsupportedVersion {
+        found: u8,
+        expected: u8,
+    },
+    Truncated,
+    Corrupt,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => f.write_str("not pong-tui data"),
+            Self::UnsupportedVersion { found, expected } => {
+                write!(f, "version {found} is not supported (expected {expected})")
+            }
+            Self::Truncated => f.write_str("data is cut short"),
+            Self::Corrupt => f.write_str("data is corrupt"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Writes `name` with a one byte length, cut short at a character boundary if it is longer.
+pub(crate) fn write_name(out: &mut Vec<u8>, name: &str) {
+    let mut len = name.len().min(u8::MAX.into());
+    while !name.is_char_boundary(len) {
+        len -= 1;
+    }
+    out.push(len as u8);
+    out.extend_from_slice(&name.as_bytes()[..len]);
+}
+
+pub(crate) fn write_input(out: &mut Vec<u8>, input: PaddleInput) {
+    match input {
+        PaddleInput::Idle => out.push(0),
+        PaddleInput::Move(speed) => {
+            out.push(1);
+            out.extend_from_slice(&speed.to_le_bytes());
+        }
+        PaddleInput::Target(y) => {
+            out.push(2);
+            out.extend_from_slice(&y.to_le_bytes());
+        }
+    }
+}
+
+pub(crate) fn write_settings(out: &mut Vec<u8>, settings: &GameSettings) {
+    out.extend_from_slice(&settings.rules.target.to_le_bytes());
+    out.push(u8::from(settings.rules.win_by_two));
+    out.extend_from_slice(&settings.ball_speed.to_le_bytes());
+    out.extend_from_slice(&settings.paddle_size.to_le_bytes());
+}
+
+/// Reads data written with the functions above, front to back.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Checks for `magic` followed by the `version` byte.
+    pub(crate) fn header(&mut self, magic: &[u8], version: u8) -> Result<(), DecodeError> {
+        if self.take(magic.len())? != magic {
+            return Err(DecodeError::BadMagic);
+        }
+        let found = self.u8()?;
+        if found != version {
+            return Err(DecodeError::UnsupportedVersion {
+                found,
+                expected: version,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < n {
+            return Err(DecodeError::Truncated);
+        }
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.take(N)?.try_into().expect("took exactly N bytes"))
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, DecodeError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, DecodeError> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    pub(crate) fn f64(&mut self) -> Result<f64, DecodeError> {
+        self.array().map(f64::from_le_bytes)
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool, DecodeError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DecodeError::Corrupt),
+        }
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::Corrupt)
+    }
+
+    pub(crate) fn name(&mut self) -> Result<String, DecodeError> {
+        let len = self.u8()?;
+        let bytes = self.take(len.into())?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::Corrupt)
+    }
+
+    pub(crate) fn settings(&mut self) -> Result<GameSettings, DecodeError> {
+        let rules = MatchRules {
+            target: self.u32()?,
+            win_by_two: self.u8()? != 0,
+        };
+        Ok(GameSettings {
+            rules,
+            ball_speed: self.f64()?,
+            paddle_size: self.f64()?,
+        })
+    }
+
+    pub(crate) fn input(&mut self) -> Result<PaddleInput, DecodeError> {
+        match self.u8()? {
+            0 => Ok(PaddleInput::Idle),
+            1 => self.f64().map(PaddleInput::Move),
+            2 => self.f64().map(PaddleInput::Target),
+            _ => Err(DecodeError::Corrupt),
+        }
+    }
+}
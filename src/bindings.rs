@@ -0,0 +1,436 @@
+use std::{collections::BTreeMap, fmt, fs, path::PathBuf, str::FromStr};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail, eyre},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::paths;
+
+/// Name of the key bindings file inside [`paths::config_dir`].
+const FILE_NAME: &str = "bindings.toml";
+
+/// Something a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    P1Up,
+    P1Down,
+    P2Up,
+    P2Down,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    Confirm,
+    Back,
+    Pause,
+    Quit,
+    Resolution,
+    SeekBack,
+    SeekForward,
+    StepBack,
+    StepForward,
+    Faster,
+    Slower,
+}
+
+impl Action {
+    pub const ALL: [Self; 19] = [
+        Self::P1Up,
+        Self::P1Down,
+        Self::P2Up,
+        Self::P2Down,
+        Self::MenuUp,
+        Self::MenuDown,
+        Self::MenuLeft,
+        Self::MenuRight,
+        Self::Confirm,
+        Self::Back,
+        Self::Pause,
+        Self::Quit,
+        Self::Resolution,
+        Self::SeekBack,
+        Self::SeekForward,
+        Self::StepBack,
+        Self::StepForward,
+        Self::Faster,
+        Self::Slower,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::P1Up => "Left paddle up",
+            Self::P1Down => "Left paddle down",
+            Self::P2Up => "Right paddle up",
+            Self::P2Down => "Right paddle down",
+            Self::MenuUp => "Menu up",
+            Self::MenuDown => "Menu down",
+            Self::MenuLeft => "Menu left",
+            Self::MenuRight => "Menu right",
+            Self::Confirm => "Confirm",
+            Self::Back => "Back",
+            Self::Pause => "Pause",
+            Self::Quit => "Quit",
+            Self::Resolution => "Switch drawing resolution",
+            Self::SeekBack => "Replay: jump back",
+            Self::SeekForward => "Replay: jump forward",
+            Self::StepBack => "Replay: previous frame",
+            Self::StepForward => "Replay: next frame",
+            Self::Faster => "Replay: faster",
+            Self::Slower => "Replay: slower",
+        }
+    }
+
+    fn default_keys(self) -> Vec<KeyBinding> {
+        let keys: &[KeyCode] = match self {
+            Self::P1Up => &[KeyCode::Char('w')],
+            Self::P1Down => &[KeyCode::Char('s')],
+            Self::P2Up => &[KeyCode::Up],
+            Self::P2Down => &[KeyCode::Down],
+            Self::MenuUp => &[KeyCode::Up, KeyCode::Char('k')],
+            Self::MenuDown => &[KeyCode::Down, KeyCode::Char('j')],
+            Self::MenuLeft => &[KeyCode::Left, KeyCode::Char('h')],
+            Self::MenuRight => &[KeyCode::Right, KeyCode::Char('l')],
+            Self::Confirm => &[KeyCode::Enter],
+            Self::Back => &[KeyCode::Esc],
+            Self::Pause => &[KeyCode::Char('p'), KeyCode::Char(' '), KeyCode::Esc],
+            Self::Quit => &[KeyCode::Char('q')],
+            Self::Resolution => &[KeyCode::Char('v')],
+            Self::SeekBack => &[KeyCode::Left, KeyCode::Char('h')],
+            Self::SeekForward => &[KeyCode::Right, KeyCode::Char('l')],
+            Self::StepBack => &[KeyCode::Char(',')],
+            Self::StepForward => &[KeyCode::Char('.')],
+            Self::Faster => &[KeyCode::Up, KeyCode::Char('+')],
+            Self::Slower => &[KeyCode::Down, KeyCode::Char('-')],
+        };
+        keys.iter().map(|&code| KeyBinding::new(code)).collect()
+    }
+}
+
+/// A key, with the modifiers that have to be held along with it.
+///
+/// Letters are case-insensitive, and only Ctrl and Alt count as modifiers, so Shift never gets
+/// in the way. Written as e.g. `w`, `Up`, `Space` or `Ctrl+x` in the bindings file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode) -> Self {
+        Self {
+            code: normalize(code),
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    /// The binding `key` would trigger.
+    pub fn from_event(key: KeyEvent) -> Self {
+        Self {
+            code: normalize(key.code),
+            modifiers: key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    pub fn matches(&self, key: KeyEvent) -> bool {
+        *self == Self::from_event(key)
+    }
+}
+
+/// Folds letters to lower case, so a key stays the same key while Shift comes and goes.
+pub fn normalize(code: KeyCode) -> KeyCode {
+    match code {
+        KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+        code => code,
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::Up => f.write_str("Up"),
+            KeyCode::Down => f.write_str("Down"),
+            KeyCode::Left => f.write_str("Left"),
+            KeyCode::Right => f.write_str("Right"),
+            KeyCode::Enter => f.write_str("Enter"),
+            KeyCode::Esc => f.write_str("Esc"),
+            KeyCode::Tab => f.write_str("Tab"),
+            KeyCode::Backspace => f.write_str("Backspace"),
+            KeyCode::Delete => f.write_str("Delete"),
+            KeyCode::Insert => f.write_str("Insert"),
+            KeyCode::Home => f.write_str("Home"),
+            KeyCode::End => f.write_str("End"),
+            KeyCode::PageUp => f.write_str("PageUp"),
+            KeyCode::PageDown => f.write_str("PageDown"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = color_eyre::Report;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text.trim();
+        loop {
+            if let Some(key) = strip_prefix_ignore_case(rest, "ctrl+") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = key;
+            } else if let Some(key) = strip_prefix_ignore_case(rest, "alt+") {
+                modifiers |= KeyModifiers::ALT;
+                rest = key;
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').map(str::parse) {
+                    Some(Ok(n @ 1..=12)) => KeyCode::F(n),
+                    _ => bail!("unknown key {text:?}"),
+                },
+            },
+        };
+        Ok(Self {
+            code: normalize(code),
+            modifiers,
+        })
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &text[prefix.len()..])
+}
+
+impl Serialize for KeyBinding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyBinding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// The keys bound to every [`Action`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bindings {
+    keys: BTreeMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .iter()
+                .map(|&action| (action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl Bindings {
+    /// Loads the bindings file, using the default keys for any action it leaves out.
+    ///
+    /// A missing file is not an error: it just means nothing has been rebound yet.
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(error) => return Err(error).wrap_err_with(|| format!("reading {path:?}")),
+        };
+        let loaded: Self = toml::from_str(&text).wrap_err_with(|| format!("parsing {path:?}"))?;
+
+        let mut bindings = Self::default();
+        bindings.keys.extend(loaded.keys);
+        Ok(bindings)
+    }
+
+    /// Writes every binding to the bindings file.
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).wrap_err_with(|| format!("creating {dir:?}"))?;
+        }
+        let text = toml::to_string(self)?;
+        fs::write(&path, text).wrap_err_with(|| format!("writing {path:?}"))
+    }
+
+    pub fn path() -> Result<PathBuf> {
+        paths::config_dir()
+            .map(|dir| dir.join(FILE_NAME))
+            .ok_or_else(|| eyre!("neither XDG_CONFIG_HOME nor HOME is set"))
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyBinding] {
+        self.keys.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether `key` triggers `action`.
+    pub fn matches(&self, action: Action, key: KeyEvent) -> bool {
+        self.keys(action).iter().any(|binding| binding.matches(key))
+    }
+
+    /// The first of `actions` that `key` triggers.
+    pub fn find(&self, key: KeyEvent, actions: &[Action]) -> Option<Action> {
+        actions
+            .iter()
+            .copied()
+            .find(|&action| self.matches(action, key))
+    }
+
+    /// Binds `action` to `key` alone.
+    pub fn set(&mut self, action: Action, key: KeyBinding) {
+        self.keys.insert(action, vec![key]);
+    }
+
+    /// Binds `action` to its default keys again.
+    pub fn reset(&mut self, action: Action) {
+        self.keys.insert(action, action.default_keys());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyBinding {
+        KeyBinding { code, modifiers }
+    }
+
+    #[test]
+    fn keys_parse_with_their_modifiers() {
+        let cases = [
+            ("w", key(KeyCode::Char('w'), KeyModifiers::NONE)),
+            ("+", key(KeyCode::Char('+'), KeyModifiers::NONE)),
+            ("Ctrl+x", key(KeyCode::Char('x'), KeyModifiers::CONTROL)),
+            ("Alt+Up", key(KeyCode::Up, KeyModifiers::ALT)),
+            (
+                "Ctrl+Alt+Space",
+                key(
+                    KeyCode::Char(' '),
+                    KeyModifiers::CONTROL | KeyModifiers::ALT,
+                ),
+            ),
+            (
+                "alt+ctrl+F5",
+                key(KeyCode::F(5), KeyModifiers::CONTROL | KeyModifiers::ALT),
+            ),
+            ("Ctrl++", key(KeyCode::Char('+'), KeyModifiers::CONTROL)),
+            ("PageDown", key(KeyCode::PageDown, KeyModifiers::NONE)),
+            (" Esc ", key(KeyCode::Esc, KeyModifiers::NONE)),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(text.parse::<KeyBinding>().unwrap(), expected, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn names_and_letters_ignore_case() {
+        for text in ["W", "w"] {
+            assert_eq!(
+                text.parse::<KeyBinding>().unwrap(),
+                KeyBinding::new(KeyCode::Char('w'))
+            );
+        }
+        for text in ["Ctrl+Enter", "CTRL+ENTER", "ctrl+enter", "cTrL+eNtEr"] {
+            assert_eq!(
+                text.parse::<KeyBinding>().unwrap(),
+                key(KeyCode::Enter, KeyModifiers::CONTROL),
+                "{text:?}"
+            );
+        }
+        assert_eq!(
+            "f12".parse::<KeyBinding>().unwrap(),
+            KeyBinding::new(KeyCode::F(12))
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        for text in [
+            "", "  ", "Ctrl+", "Shift+a", "Escape", "F0", "F13", "Fx", "ab", "Ctrl-x",
+        ] {
+            assert!(text.parse::<KeyBinding>().is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn keys_print_as_they_parse() {
+        let mut keys: Vec<KeyBinding> = Action::ALL
+            .into_iter()
+            .flat_map(Action::default_keys)
+            .collect();
+        keys.extend([
+            key(KeyCode::Char('x'), KeyModifiers::CONTROL),
+            key(KeyCode::Char(' '), KeyModifiers::ALT),
+            key(KeyCode::F(1), KeyModifiers::CONTROL | KeyModifiers::ALT),
+            KeyBinding::new(KeyCode::Backspace),
+            KeyBinding::new(KeyCode::Tab),
+            KeyBinding::new(KeyCode::Home),
+        ]);
+        for binding in keys {
+            let text = binding.to_string();
+            assert_eq!(text.parse::<KeyBinding>().unwrap(), binding, "{text:?}");
+        }
+        assert_eq!(
+            key(
+                KeyCode::Char(' '),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            )
+            .to_string(),
+            "Ctrl+Alt+Space"
+        );
+    }
+
+    #[test]
+    fn shift_does_not_change_the_key() {
+        let ctrl_x: KeyBinding = "Ctrl+x".parse().unwrap();
+        let shifted = KeyEvent::new(
+            KeyCode::Char('X'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        assert!(ctrl_x.matches(shifted));
+        assert!(!ctrl_x.matches(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)));
+    }
+}
@@ -0,0 +1,337 @@
+use std::{fmt::Display, str::FromStr};
+
+use color_eyre::{
+    Result,
+    eyre::{bail, ensure, eyre},
+};
+use pong_core::{Difficulty, LinkConditions};
+
+use crate::{config::Config, net::Netcode, theme::Theme};
+
+pub const USAGE: &str = "\
+Usage: pong-tui [OPTIONS]
+
+Options:
+  --mode <MODE>            Skip the menu: menu, vs-ai, two-player, host, join
+                           or watch [default: menu]
+  --difficulty <LEVEL>     Computer strength: easy, normal, hard or impossible
+  --target <POINTS>        Points needed to win a match
+  --win-by-two             Make the winner lead by two points
+  --ball-speed <PERCENT>   Ball speed, in percent of normal
+  --paddle-size <PERCENT>  Paddle height, in percent of normal
+  --tick-rate <HZ>         Simulation steps per second
+  --theme <THEME>          Colours: classic, phosphor, amber or ocean
+  --seed <SEED>            Seed for everything random in a match, shown when it ends
+  --port <PORT>            Port to host network matches on
+  --address <HOST:PORT>    Host to join with --mode join or watch
+  --netcode <NETCODE>      Network play: host (the host runs the match) or rollback (both
+                           ends do, over UDP)
+  --input-delay <TICKS>    Ticks before your input takes effect in a rollback match
+  --rollback-window <TICKS>
+                           Most ticks a rollback match guesses ahead of the other player
+  --headless               Play a computer-vs-computer match without a terminal and print
+                           the result
+  --latency <MS>           With --headless, play it as a rollback match between two peers
+                           over a simulated link this slow
+  --jitter <MS>            Up to this much extra, random latency on the simulated link
+  --loss <PERCENT>         Share of datagrams the simulated link loses
+  -h, --help               Print this help
+  -V, --version            Print the version
+
+Settings given here, and --seed, override the settings file for this run only. Overridden
+settings are written to the file only if it is saved from the Settings screen.";
+
+/// Where the app opens.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StartMode {
+    #[default]
+    Menu,
+    VsAi,
+    TwoPlayer,
+    /// Host a match over the network.
+    Host,
+    /// Join a match hosted over the network.
+    Join,
+    /// Watch a match hosted over the network.
+    Watch,
+}
+
+impl FromStr for StartMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "menu" => Ok(Self::Menu),
+            "vs-ai" => Ok(Self::VsAi),
+            "two-player" => Ok(Self::TwoPlayer),
+            "host" => Ok(Self::Host),
+            "join" => Ok(Self::Join),
+            "watch" => Ok(Self::Watch),
+            _ => Err(format!("unknown mode {name:?}")),
+        }
+    }
+}
+
+/// What the command line asks for.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Cli {
+    pub mode: StartMode,
+    pub difficulty: Option<Difficulty>,
+    pub target: Option<u32>,
+    pub win_by_two: bool,
+    pub ball_speed: Option<u32>,
+    pub paddle_size: Option<u32>,
+    pub tick_rate: Option<u32>,
+    pub theme: Option<Theme>,
+    pub seed: Option<u64>,
+    pub port: Option<u16>,
+    pub address: Option<String>,
+    pub netcode: Option<Netcode>,
+    pub input_delay: Option<u32>,
+    pub rollback_window: Option<u32>,
+    pub headless: bool,
+    pub latency: Option<f64>,
+    pub jitter: Option<f64>,
+    pub loss: Option<f64>,
+    pub help: bool,
+    pub version: bool,
+}
+
+impl Cli {
+    /// Parses the arguments after the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // both `--target 5` and `--target=5` are accepted
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| eyre!("{flag} needs a value"))
+            };
+            // a switch is on by being given, so `--headless=false` is a mistake, not a value
+            let switch = || -> Result<bool> {
+                ensure!(inline.is_none(), "{flag} doesn't take a value");
+                Ok(true)
+            };
+            match flag {
+                "--mode" => cli.mode = parse(flag, &value()?)?,
+                "--difficulty" => cli.difficulty = Some(parse(flag, &value()?)?),
+                "--target" => cli.target = Some(parse(flag, &value()?)?),
+                "--ball-speed" => cli.ball_speed = Some(parse(flag, &value()?)?),
+                "--paddle-size" => cli.paddle_size = Some(parse(flag, &value()?)?),
+                "--tick-rate" => cli.tick_rate = Some(parse(flag, &value()?)?),
+                "--theme" => cli.theme = Some(parse(flag, &value()?)?),
+                "--seed" => cli.seed = Some(parse(flag, &value()?)?),
+                "--port" => cli.port = Some(parse(flag, &value()?)?),
+                "--address" => cli.address = Some(value()?),
+                "--netcode" => cli.netcode = Some(parse(flag, &value()?)?),
+                "--input-delay" => cli.input_delay = Some(parse(flag, &value()?)?),
+                "--rollback-window" => cli.rollback_window = Some(parse(flag, &value()?)?),
+                "--latency" => cli.latency = Some(parse(flag, &value()?)?),
+                "--jitter" => cli.jitter = Some(parse(flag, &value()?)?),
+                "--loss" => cli.loss = Some(parse(flag, &value()?)?),
+                "--win-by-two" => cli.win_by_two = switch()?,
+                "--headless" => cli.headless = switch()?,
+                "-h" | "--help" => cli.help = switch()?,
+                "-V" | "--version" => cli.version = switch()?,
+                _ => bail!("unexpected argument {arg:?}"),
+            }
+        }
+        Ok(cli)
+    }
+
+    /// Applies the overridden settings to `config`, checking they are in range.
+    pub fn apply(&self, config: &mut Config) -> Result<()> {
+        if let Some(difficulty) = self.difficulty {
+            config.difficulty = difficulty;
+        }
+        if let Some(target) = self.target {
+            config.target_score = target;
+        }
+        if self.win_by_two {
+            config.win_by_two = true;
+        }
+        if let Some(ball_speed) = self.ball_speed {
+            config.ball_speed = ball_speed;
+        }
+        if let Some(paddle_size) = self.paddle_size {
+            config.paddle_size = paddle_size;
+        }
+        if let Some(tick_rate) = self.tick_rate {
+            config.tick_rate = tick_rate;
+        }
+        if let Some(theme) = self.theme {
+            config.theme = theme;
+        }
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(address) = &self.address {
+            config.join_address = address.clone();
+        }
+        if let Some(netcode) = self.netcode {
+            config.netcode = netcode;
+        }
+        if let Some(input_delay) = self.input_delay {
+            config.input_delay = input_delay;
+        }
+        if let Some(rollback_window) = self.rollback_window {
+            config.rollback_window = rollback_window;
+        }
+        config.validate()
+    }
+
+    /// The simulated link asked for with `--latency`, `--jitter` and `--loss`, if any.
+    pub fn link(&self) -> Result<Option<LinkConditions>> {
+        if self.latency.is_none() && self.jitter.is_none() && self.loss.is_none() {
+            return Ok(None);
+        }
+        let conditions = LinkConditions {
+            latency: self.latency.unwrap_or(0.0) / 1000.0,
+            jitter: self.jitter.unwrap_or(0.0) / 1000.0,
+            loss: self.loss.unwrap_or(0.0) / 100.0,
+        };
+        ensure!(
+            conditions.latency >= 0.0 && conditions.jitter >= 0.0,
+            "--latency and --jitter can't be negative"
+        );
+        ensure!(
+            (0.0..1.0).contains(&conditions.loss),
+            "--loss must be at least 0 and below 100"
+        );
+        Ok(Some(conditions))
+    }
+}
+
+fn parse<T>(flag: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .parse()
+        .map_err(|error| eyre!("invalid value {value:?} for {flag}: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Cli> {
+        Cli::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_open_the_menu() {
+        assert_eq!(parse_args(&[]).unwrap(), Cli::default());
+    }
+
+    #[test]
+    fn values_follow_their_flag_or_an_equals_sign() {
+        let cli = parse_args(&[
+            "--mode",
+            "vs-ai",
+            "--difficulty=hard",
+            "--target",
+            "5",
+            "--seed=42",
+            "--address",
+            "10.0.0.2:4000",
+            "--netcode=rollback",
+            "--latency",
+            "80.5",
+            "--win-by-two",
+            "--headless",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli,
+            Cli {
+                mode: StartMode::VsAi,
+                difficulty: Some(Difficulty::Hard),
+                target: Some(5),
+                seed: Some(42),
+                address: Some("10.0.0.2:4000".to_string()),
+                netcode: Some(Netcode::Rollback),
+                latency: Some(80.5),
+                win_by_two: true,
+                headless: true,
+                ..Cli::default()
+            }
+        );
+        assert!(parse_args(&["-h"]).unwrap().help);
+        assert!(parse_args(&["--version"]).unwrap().version);
+    }
+
+    #[test]
+    fn switches_reject_values() {
+        for arg in [
+            "--win-by-two=false",
+            "--win-by-two=true",
+            "--headless=",
+            "--help=1",
+            "--version=no",
+        ] {
+            assert!(parse_args(&[arg]).is_err(), "{arg:?}");
+        }
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        let cases: &[&[&str]] = &[
+            &["--target"],
+            &["--target", "five"],
+            &["--target=-1"],
+            &["--mode", "solo"],
+            &["--difficulty", "brutal"],
+            &["--port", "70000"],
+            &["--frobnicate"],
+            &["vs-ai"],
+        ];
+        for args in cases {
+            assert!(parse_args(args).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn overrides_are_range_checked() {
+        let mut config = Config::default();
+        let cli = parse_args(&["--target", "7", "--win-by-two", "--tick-rate=120"]).unwrap();
+        cli.apply(&mut config).unwrap();
+        assert_eq!(config.target_score, 7);
+        assert!(config.win_by_two);
+        assert_eq!(config.tick_rate, 120);
+
+        for args in [
+            ["--target", "0"],
+            ["--ball-speed", "500"],
+            ["--tick-rate", "1"],
+        ] {
+            let cli = parse_args(&args).unwrap();
+            assert!(cli.apply(&mut Config::default()).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn the_simulated_link_is_checked() {
+        assert_eq!(parse_args(&[]).unwrap().link().unwrap(), None);
+        let link = parse_args(&["--latency", "80", "--loss", "10"])
+            .unwrap()
+            .link()
+            .unwrap()
+            .unwrap();
+        assert_eq!(link.latency, 0.08);
+        assert_eq!(link.jitter, 0.0);
+        assert_eq!(link.loss, 0.1);
+
+        for args in [["--latency", "-5"], ["--loss", "100"], ["--loss", "-1"]] {
+            assert!(parse_args(&args).unwrap().link().is_err(), "{args:?}");
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::{fs, io, path::PathBuf};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, ensure, eyre},
+};
+use pong_core::{Difficulty, GameSettings, MatchRules, RollbackSettings};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    net::{self, Netcode},
+    paths,
+    pixels::Resolution,
+    theme::Theme,
+};
+
+/// Name of the settings file inside [`paths::config_dir`].
+const FILE_NAME: &str = "config.toml";
+
+/// Allowed values of the numeric settings.
+pub const TARGET_SCORES: std::ops::RangeInclusive<u32> = 1..=99;
+pub const PERCENTAGES: std::ops::RangeInclusive<u32> = 50..=200;
+pub const TICK_RATES: std::ops::RangeInclusive<u32> = 30..=240;
+pub const INPUT_DELAYS: std::ops::RangeInclusive<u32> = 0..=10;
+pub const ROLLBACK_WINDOWS: std::ops::RangeInclusive<u32> = 1..=30;
+
+/// Everything the player can change on the Settings screen.
+///
+/// Any setting left out of the file keeps its default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Points needed to win a match.
+    pub target_score: u32,
+    /// Whether the winner must also lead by two points.
+    pub win_by_two: bool,
+    /// Ball speed, in percent of normal.
+    pub ball_speed: u32,
+    /// Paddle height, in percent of normal.
+    pub paddle_size: u32,
+    /// Strength of the computer opponent.
+    #[serde(with = "as_string")]
+    pub difficulty: Difficulty,
+    pub theme: Theme,
+    /// Simulation steps per second.
+    pub tick_rate: u32,
+    /// How finely the ball and paddles are drawn.
+    pub resolution: Resolution,
+    /// Seed to play every match with, for reproducing one; a new seed each match if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Port to host network matches on.
+    pub port: u16,
+    /// Where the last network match was joined, offered again on the Join Game screen.
+    pub join_address: String,
+    /// How network matches are kept in step; both ends must use the same.
+    pub netcode: Netcode,
+    /// Ticks before a local input takes effect in a rollback match.
+    pub input_delay: u32,
+    /// Most ticks a rollback match guesses ahead of the other player's inputs.
+    pub rollback_window: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let rules = MatchRules::default();
+        Self {
+            target_score: rules.target,
+            win_by_two: rules.win_by_two,
+            ball_speed: 100,
+            paddle_size: 100,
+            difficulty: Difficulty::default(),
+            theme: Theme::default(),
+            tick_rate: 60,
+            resolution: Resolution::default(),
+            seed: None,
+            port: net::DEFAULT_PORT,
+            join_address: format!("127.0.0.1:{}", net::DEFAULT_PORT),
+            netcode: Netcode::default(),
+            input_delay: RollbackSettings::default().input_delay,
+            rollback_window: RollbackSettings::default().window,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the settings file.
+    ///
+    /// Fails if the file is missing, unreadable or holds values out of range; callers are
+    /// expected to carry on with [`Config::default`] and let the player know.
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Err(eyre!("no settings file at {path:?} yet"));
+            }
+            Err(error) => return Err(error).wrap_err_with(|| format!("reading {path:?}")),
+        };
+        let config: Self = toml::from_str(&text).wrap_err_with(|| format!("parsing {path:?}"))?;
+        config
+            .validate()
+            .wrap_err_with(|| format!("checking {path:?}"))?;
+        Ok(config)
+    }
+
+    /// Writes the settings file.
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).wrap_err_with(|| format!("creating {dir:?}"))?;
+        }
+        let text = toml::to_string(self)?;
+        fs::write(&path, text).wrap_err_with(|| format!("writing {path:?}"))
+    }
+
+    pub fn path() -> Result<PathBuf> {
+        paths::config_dir()
+            .map(|dir| dir.join(FILE_NAME))
+            .ok_or_else(|| eyre!("neither XDG_CONFIG_HOME nor HOME is set"))
+    }
+
+    /// Checks that every setting is within its allowed range.
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            TARGET_SCORES.contains(&self.target_score),
+            "target_score must be within {TARGET_SCORES:?}"
+        );
+        ensure!(
+            PERCENTAGES.contains(&self.ball_speed),
+            "ball_speed must be within {PERCENTAGES:?}"
+        );
+        ensure!(
+            PERCENTAGES.contains(&self.paddle_size),
+            "paddle_size must be within {PERCENTAGES:?}"
+        );
+        ensure!(
+            TICK_RATES.contains(&self.tick_rate),
+            "tick_rate must be within {TICK_RATES:?}"
+        );
+        ensure!(
+            INPUT_DELAYS.contains(&self.input_delay),
+            "input_delay must be within {INPUT_DELAYS:?}"
+        );
+        ensure!(
+            ROLLBACK_WINDOWS.contains(&self.rollback_window),
+            "rollback_window must be within {ROLLBACK_WINDOWS:?}"
+        );
+        Ok(())
+    }
+
+    /// The settings a match is started with.
+    pub fn game_settings(&self) -> GameSettings {
+        GameSettings {
+            rules: MatchRules {
+                target: self.target_score,
+                win_by_two: self.win_by_two,
+            },
+            ball_speed: f64::from(self.ball_speed) / 100.0,
+            paddle_size: f64::from(self.paddle_size) / 100.0,
+        }
+    }
+
+    /// How a rollback match is played from this end.
+    pub fn rollback_settings(&self) -> RollbackSettings {
+        RollbackSettings {
+            input_delay: self.input_delay,
+            window: self.rollback_window,
+        }
+    }
+}
+
+/// Reads and writes a value through its `Display` and `FromStr` implementations, for types
+/// from `pong_core`, which doesn't depend on serde.
+pub mod as_string {
+    use std::{fmt::Display, str::FromStr};
+
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_valid() {
+        Config::default().validate().unwrap();
+    }
+
+    #[test]
+    fn every_setting_is_checked_at_both_ends_of_its_range() {
+        type Setting = fn(&mut Config) -> &mut u32;
+        let settings: [(Setting, std::ops::RangeInclusive<u32>); 6] = [
+            (|config| &mut config.target_score, TARGET_SCORES),
+            (|config| &mut config.ball_speed, PERCENTAGES),
+            (|config| &mut config.paddle_size, PERCENTAGES),
+            (|config| &mut config.tick_rate, TICK_RATES),
+            (|config| &mut config.input_delay, INPUT_DELAYS),
+            (|config| &mut config.rollback_window, ROLLBACK_WINDOWS),
+        ];
+        for (setting, range) in settings {
+            let check = |value| {
+                let mut config = Config::default();
+                *setting(&mut config) = value;
+                config.validate()
+            };
+            assert!(check(*range.start()).is_ok(), "{range:?}");
+            assert!(check(*range.end()).is_ok(), "{range:?}");
+            assert!(check(*range.end() + 1).is_err(), "{range:?}");
+            if let Some(below) = range.start().checked_sub(1) {
+                assert!(check(below).is_err(), "{range:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn files_round_trip_and_fill_in_defaults() {
+        let config = Config {
+            target_score: 5,
+            win_by_two: true,
+            seed: Some(7),
+            ..Config::default()
+        };
+        let text = toml::to_string(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&text).unwrap(), config);
+
+        let partial: Config = toml::from_str("target_score = 3").unwrap();
+        assert_eq!(
+            partial,
+            Config {
+                target_score: 3,
+                ..Config::default()
+            }
+        );
+        assert!(toml::from_str::<Config>("target = 3").is_err());
+    }
+}
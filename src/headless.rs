@@ -0,0 +1,121 @@
+use color_eyre::{Result, eyre::ensure};
+use pong_core::{Ai, GameState, Inputs, LinkConditions, Loopback, Rollback, Side};
+
+use crate::config::Config;
+
+/// Longest match a headless run plays before giving up, in simulated seconds. Two flawless
+/// computers would otherwise rally forever.
+const MAX_MATCH_TIME: f64 = 60.0 * 60.0;
+
+/// Plays a match between two computer players at the configured difficulty, as fast as
+/// possible, and prints how it went.
+pub fn run(config: &Config, seed: u64) {
+    let dt = 1.0 / f64::from(config.tick_rate);
+    let settings = config.difficulty.settings();
+    let mut game = GameState::new(config.game_settings(), seed);
+    let mut left = Ai::new(Side::Left, settings, game.side_rng(Side::Left));
+    let mut right = Ai::new(Side::Right, settings, game.side_rng(Side::Right));
+
+    let mut ticks = 0u64;
+    while game.winner().is_none() && ticks as f64 * dt < MAX_MATCH_TIME {
+        let inputs = Inputs {
+            left: left.input(&game, dt),
+            right: right.input(&game, dt),
+        };
+        game.step(inputs, dt);
+        ticks += 1;
+    }
+
+    let score = game.score();
+    let winner = match game.winner() {
+        Some(Side::Left) => "Left wins",
+        Some(Side::Right) => "Right wins",
+        None => "No winner",
+    };
+    println!(
+        "{winner} {}-{} after {:.1}s ({ticks} ticks, {} computers, seed {seed})",
+        score.left,
+        score.right,
+        ticks as f64 * dt,
+        config.difficulty,
+    );
+}
+
+/// Plays the same kind of match as [`run`], but as a rollback match between two peers talking
+/// over a simulated link with the given `conditions`, and checks both peers end up agreeing.
+pub fn run_rollback(config: &Config, seed: u64, conditions: LinkConditions) -> Result<()> {
+    let dt = 1.0 / f64::from(config.tick_rate);
+    let settings = config.difficulty.settings();
+    let game = GameState::new(config.game_settings(), seed);
+    let (left_link, right_link) = Loopback::pair(conditions, seed);
+    let mut peers = [(Side::Left, left_link), (Side::Right, right_link)].map(|(side, link)| {
+        let session = Rollback::new(
+            config.game_settings(),
+            seed,
+            config.tick_rate,
+            side,
+            config.rollback_settings(),
+        );
+        let ai = Ai::new(side, settings, game.side_rng(side));
+        (session, ai, link)
+    });
+
+    let mut ticks = 0u64;
+    let finished = |peers: &[(Rollback, Ai, Loopback); 2]| {
+        peers
+            .iter()
+            .all(|(session, _, _)| session.confirmed().winner().is_some())
+    };
+    while !finished(&peers) && ticks as f64 * dt < MAX_MATCH_TIME {
+        peers[0].2.advance(dt);
+        for (session, ai, link) in &mut peers {
+            session.exchange(link)?;
+            let input = ai.input(session.game(), dt);
+            session.advance(input);
+        }
+        ticks += 1;
+    }
+
+    let [(left, ..), (right, ..)] = &peers;
+    ensure!(
+        left.confirmed() == right.confirmed() || !finished(&peers),
+        "the peers disagree on how the match ended"
+    );
+    for session in [left, right] {
+        ensure!(
+            session.replay().final_state() == *session.confirmed(),
+            "a peer's replay doesn't play back the match it confirmed"
+        );
+    }
+    let game = left.confirmed();
+    let score = game.score();
+    let winner = match game.winner() {
+        Some(Side::Left) => "Left wins",
+        Some(Side::Right) => "Right wins",
+        None => "No winner",
+    };
+    println!(
+        "{winner} {}-{} after {:.1}s ({} ticks, {} computers, seed {seed})",
+        score.left,
+        score.right,
+        game.stats().duration,
+        left.confirmed_tick(),
+        config.difficulty,
+    );
+    println!(
+        "Link: {:.0} ms latency, {:.0} ms jitter, {:.0}% loss; input delay {} ticks, window {} ticks",
+        conditions.latency * 1000.0,
+        conditions.jitter * 1000.0,
+        conditions.loss * 100.0,
+        config.input_delay,
+        config.rollback_window,
+    );
+    for (name, session) in [("Left", left), ("Right", right)] {
+        let stats = session.stats();
+        println!(
+            "{name} peer: {} rollbacks, {} ticks played again, waited {} ticks",
+            stats.rollbacks, stats.resimulated, stats.stalls
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use std::{fs, io, path::PathBuf};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, eyre},
+};
+use pong_core::{Difficulty, GameState, Score};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{Opponent, config::as_string, date, paths};
+
+/// Name of the match history file inside [`paths::data_dir`].
+const FILE_NAME: &str = "history.toml";
+
+/// One finished match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchRecord {
+    /// When it ended, in seconds since the Unix epoch.
+    pub played: u64,
+    /// Who played the right paddle; the left one is always the person at the keyboard.
+    #[serde(with = "as_string")]
+    pub opponent: Opponent,
+    pub left: u32,
+    pub right: u32,
+    /// Length of the match, in seconds.
+    pub duration: f64,
+    pub longest_rally: u32,
+}
+
+impl MatchRecord {
+    /// The record of `game`, which has just been won.
+    pub fn new(game: &GameState, opponent: Opponent) -> Self {
+        let Score { left, right } = game.score();
+        Self {
+            played: date::now(),
+            opponent,
+            left,
+            right,
+            duration: game.stats().duration,
+            longest_rally: game.stats().longest_rally,
+        }
+    }
+
+    /// Whether the left player, the one at the keyboard in a 1 player game, won.
+    pub fn left_won(&self) -> bool {
+        self.left > self.right
+    }
+}
+
+/// Every finished match, oldest first.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default, rename = "match")]
+    pub matches: Vec<MatchRecord>,
+}
+
+impl History {
+    /// Loads the history file; a missing one just means no matches have been played yet.
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error).wrap_err_with(|| format!("reading {path:?}")),
+        };
+        toml::from_str(&text).wrap_err_with(|| format!("parsing {path:?}"))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).wrap_err_with(|| format!("creating {dir:?}"))?;
+        }
+        let text = toml::to_string(self)?;
+        fs::write(&path, text).wrap_err_with(|| format!("writing {path:?}"))
+    }
+
+    pub fn path() -> Result<PathBuf> {
+        paths::data_dir()
+            .map(|dir| dir.join(FILE_NAME))
+            .ok_or_else(|| eyre!("neither XDG_DATA_HOME nor HOME is set"))
+    }
+
+    /// Adds `record` to the history file.
+    ///
+    /// A history file that can't be read is left alone rather than replaced.
+    pub fn append(record: MatchRecord) -> Result<()> {
+        let mut history = Self::load()?;
+        history.matches.push(record);
+        history.save()
+    }
+
+    /// Matches played and won against the computer at `difficulty`.
+    fn record_against(&self, difficulty: Difficulty) -> (usize, usize) {
+        let matches = self
+            .matches
+            .iter()
+            .filter(|record| record.opponent == Opponent::Computer(difficulty));
+        matches.fold((0, 0), |(played, won), record| {
+            (played + 1, won + usize::from(record.left_won()))
+        })
+    }
+}
+
+/// The High Scores screen: win rates against the computer, the longest rally and the most
+/// recent matches.
+#[derive(Debug, Default)]
+pub struct HighScores {
+    history: History,
+    /// Trouble reading the history, shown at the bottom.
+    status: Option<String>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        match History::load() {
+            Ok(history) => Self {
+                history,
+                status: None,
+            },
+            Err(error) => Self {
+                history: History::default(),
+                status: Some(crate::summary(&error)),
+            },
+        }
+    }
+
+    pub fn render(&self, hint: &str, frame: &mut Frame, area: Rect) {
+        let title = Line::from(" High Scores ").bold().blue().centered();
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let rates_height = Difficulty::ALL.len() as u16 + 1;
+        let [rates_area, record_area, recent_area, hint_area] = Layout::vertical([
+            Constraint::Length(rates_height),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let rates = Difficulty::ALL.iter().map(|&difficulty| {
+            let (played, won) = self.history.record_against(difficulty);
+            let rate = match played {
+                0 => "-".to_string(),
+                _ => format!("{:.0}%", won as f64 * 100.0 / played as f64),
+            };
+            Row::new([
+                format!("vs {difficulty}"),
+                played.to_string(),
+                won.to_string(),
+                rate,
+            ])
+        });
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+        ];
+        let rates = Table::new(rates, widths)
+            .header(Row::new(["Computer", "Played", "Won", "Win rate"]).bold());
+        frame.render_widget(rates, rates_area);
+
+        let longest = self
+            .history
+            .matches
+            .iter()
+            .max_by_key(|record| record.longest_rally);
+        let record = match longest {
+            Some(record) => format!(
+                "Longest rally: {} hits, Player 1 vs {} on {}",
+                record.longest_rally,
+                record.opponent,
+                date::format(record.played),
+            ),
+            None => "No matches played yet".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(format!("\n{record}")).centered(),
+            record_area,
+        );
+
+        let recent = self.history.matches.iter().rev().map(|record| {
+            Row::new([
+                date::format(record.played),
+                record.opponent.to_string(),
+                format!("{} - {}", record.left, record.right),
+                date::clock(record.duration),
+                record.longest_rally.to_string(),
+            ])
+        });
+        let widths = [
+            Constraint::Length(17),
+            Constraint::Fill(1),
+            Constraint::Length(7),
+            Constraint::Length(6),
+            Constraint::Length(6),
+        ];
+        let recent = Table::new(recent, widths)
+            .header(Row::new(["Date", "Opponent", "Score", "Length", "Rally"]).bold());
+        frame.render_widget(recent, recent_area);
+
+        let hint = self.status.as_deref().unwrap_or(hint);
+        frame.render_widget(Line::from(hint).dim().centered(), hint_area);
+    }
+}
@@ -0,0 +1,91 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Block, Paragraph},
+};
+
+/// Longest address that can be typed in.
+const MAX_ADDRESS_LEN: usize = 64;
+
+/// The Join Game and Watch Game screens, where the host's address is typed in.
+///
+/// Its keys are fixed, since every printable key goes into the address.
+#[derive(Debug, Default)]
+pub struct JoinScreen {
+    /// The host to join, as `host:port`.
+    pub address: String,
+    /// Whether to watch the match rather than play in it.
+    pub watch: bool,
+    /// Why joining failed last time, shown at the bottom.
+    pub status: Option<String>,
+}
+
+impl JoinScreen {
+    pub fn new(address: String, watch: bool) -> Self {
+        Self {
+            address,
+            watch,
+            status: None,
+        }
+    }
+
+    /// Types `key` into the address, returning whether it was a key for editing.
+    pub fn edit(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if self.address.len() < MAX_ADDRESS_LEN && !c.is_whitespace() {
+                    self.address.push(c);
+                }
+            }
+            KeyCode::Backspace => {
+                self.address.pop();
+            }
+            _ => return false,
+        }
+        self.status = None;
+        true
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let title = if self.watch {
+            " Watch Game "
+        } else {
+            " Join Game "
+        };
+        let title = Line::from(title).bold().blue().centered();
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [body, status_area, hint_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        let [body] = Layout::vertical([Constraint::Length(3)])
+            .flex(Flex::Center)
+            .areas(body);
+
+        let lines = vec![
+            Line::from("Address of the host"),
+            Line::default(),
+            Line::from(format!("{}█", self.address)).bold(),
+        ];
+        frame.render_widget(Paragraph::new(lines).centered(), body);
+
+        if let Some(status) = &self.status {
+            frame.render_widget(Line::from(status.as_str()).red().centered(), status_area);
+        }
+
+        let hint = if self.watch {
+            "Type host:port · Enter to watch · Esc to go back"
+        } else {
+            "Type host:port · Enter to join · Esc to go back"
+        };
+        frame.render_widget(Line::from(hint).dim().centered(), hint_area);
+    }
+}
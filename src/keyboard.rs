@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+
+use crossterm::event::{KeyEvent, KeyEventKind};
+use pong_core::PaddleInput;
+
+use crate::bindings::KeyBinding;
+
+/// How long a key press counts as held when the terminal doesn't report releases; short, so a
+/// tap nudges the paddle only a little.
+const FIRST_HOLD: f64 = 0.15;
+
+/// How long a press counts as held when it arrives while the key is still held, i.e. when the
+/// OS repeats it, bridging the gap to the next repeat, which is usually 30 to 40 ms.
+const REPEAT_HOLD: f64 = 0.1;
+
+/// Which keys are currently held down.
+///
+/// Terminals that support the keyboard enhancement protocol report key releases, so held keys
+/// are tracked exactly and any number of them can be down at once. Everywhere else only presses
+/// and OS key repeats arrive, so a first press counts as holding the key for [`FIRST_HOLD`]
+/// seconds and every repeat while it is still held for [`REPEAT_HOLD`] more. Until the OS
+/// starts repeating a held key, usually 250 to 660 ms in, the paddle pauses.
+///
+/// Keys are tracked along with their modifiers, so `Ctrl+w` and `w` are different keys.
+#[derive(Debug, Default)]
+pub struct Keyboard {
+    /// Whether key release events are reported.
+    releases: bool,
+    held: HashSet<KeyBinding>,
+    /// Seconds left on each timed hold, when releases aren't reported.
+    timed: HashMap<KeyBinding, f64>,
+}
+
+impl Keyboard {
+    pub fn new(releases: bool) -> Self {
+        Self {
+            releases,
+            ..Self::default()
+        }
+    }
+
+    /// Records a key press, repeat or release.
+    pub fn handle(&mut self, key: KeyEvent) {
+        let binding = KeyBinding::from_event(key);
+        match key.kind {
+            KeyEventKind::Press | KeyEventKind::Repeat if self.releases => {
+                self.held.insert(binding);
+            }
+            KeyEventKind::Press | KeyEventKind::Repeat => {
+                // without the protocol repeats arrive as presses too, so tell them apart by
+                // whether the key is still counted as held
+                let hold = match self.timed.get(&binding) {
+                    Some(&left) => left.max(REPEAT_HOLD),
+                    None => FIRST_HOLD,
+                };
+                self.timed.insert(binding, hold);
+            }
+            KeyEventKind::Release => {
+                // the modifiers may have been let go first
+                self.held.retain(|held| held.code != binding.code);
+            }
+        }
+    }
+
+    /// Counts timed holds down by `dt` seconds.
+    pub fn update(&mut self, dt: f64) {
+        self.timed.retain(|_, hold| {
+            *hold -= dt;
+            *hold > 0.0
+        });
+    }
+
+    /// Forgets every held key, e.g. when the keys are about to mean something else.
+    pub fn clear(&mut self) {
+        self.held.clear();
+        self.timed.clear();
+    }
+
+    pub fn is_down(&self, key: &KeyBinding) -> bool {
+        self.held.contains(key) || self.timed.contains_key(key)
+    }
+
+    /// The paddle input for the keys bound to moving `up` and `down`; holding both cancels out.
+    pub fn paddle_input(&self, up: &[KeyBinding], down: &[KeyBinding]) -> PaddleInput {
+        let any_down = |keys: &[KeyBinding]| keys.iter().any(|key| self.is_down(key));
+        match (any_down(up), any_down(down)) {
+            (true, false) => PaddleInput::Move(-1.0),
+            (false, true) => PaddleInput::Move(1.0),
+            _ => PaddleInput::Idle,
+        }
+    }
+}
@@ -1,118 +1,1449 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::{fmt, io, net::IpAddr, path::PathBuf, str::FromStr};
+
 use color_eyre::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, KeyboardEnhancementFlags, MouseButton, MouseEvent, MouseEventKind,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::supports_keyboard_enhancement,
+};
+use pong_core::{
+    ARENA_HEIGHT, ARENA_WIDTH, Ai, BALL_SIZE, Difficulty, GameState, Inputs, Message, PADDLE_WIDTH,
+    PaddleInput, Replay, Side, Snapshot,
+};
 use ratatui::{
+    DefaultTerminal, Frame,
     prelude::Rect,
     style::{Style, Stylize},
     text::Line,
-    widgets::{Block, Paragraph},
-    DefaultTerminal, Frame,
+    widgets::{Block, Clear, Paragraph},
 };
 
+mod bindings;
+mod cli;
+mod config;
+mod date;
+mod headless;
+mod history;
+mod join;
+mod keyboard;
+mod menu;
+mod net;
+mod paths;
+mod pause;
+mod pixels;
+mod rebind;
+mod replays;
+mod results;
+mod scoreboard;
+mod settings;
+mod theme;
+
+use bindings::{Action, Bindings, KeyBinding};
+use cli::{Cli, StartMode};
+use config::Config;
+use history::{HighScores, History, MatchRecord};
+use join::JoinScreen;
+use keyboard::Keyboard;
+use menu::{Menu, MenuItem};
+use net::{Client, Host, Netcode, Network, Peer};
+use pause::{PauseItem, PauseMenu, popup_area};
+use pixels::PixelGrid;
+use rebind::RebindScreen;
+use replays::{ReplayBrowser, ReplayViewer};
+use results::{ResultsItem, ResultsScreen};
+use scoreboard::Scoreboard;
+use settings::{SettingItem, SettingsScreen};
+
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
+
+    let cli = match Cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(error) => {
+            eprintln!(
+                "error: {}\n\nRun with --help to see the options.",
+                summary(&error)
+            );
+            std::process::exit(2);
+        }
+    };
+    if cli.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if cli.version {
+        println!("pong-tui {}", env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+
+    let mut warnings = Vec::new();
+    let mut config_unreadable = false;
+    let mut config = Config::load().unwrap_or_else(|error| {
+        warnings.push(error.wrap_err("Using default settings"));
+        config_unreadable = Config::path().is_ok_and(|path| path.exists());
+        Config::default()
+    });
+    let saved_config = config.clone();
+    if let Err(error) = cli.apply(&mut config) {
+        eprintln!("error: {}", summary(&error));
+        std::process::exit(2);
+    }
+    if cli.headless {
+        for warning in &warnings {
+            eprintln!("Warning: {}", summary(warning));
+        }
+        let seed = cli.seed.or(config.seed).unwrap_or_else(seed);
+        match cli.link() {
+            Ok(Some(conditions)) => return headless::run_rollback(&config, seed, conditions),
+            Ok(None) => headless::run(&config, seed),
+            Err(error) => {
+                eprintln!("error: {}", summary(&error));
+                std::process::exit(2);
+            }
+        }
+        return Ok(());
+    }
+    let bindings = Bindings::load().unwrap_or_else(|error| {
+        warnings.push(error.wrap_err("Using default keys"));
+        Bindings::default()
+    });
+
     let terminal = ratatui::init();
-    let result = App::new().run(terminal);
+    let releases = enable_key_releases();
+    enable_mouse_capture();
+    let mut app = App::new(releases, config, bindings);
+    app.saved_config = saved_config;
+    app.config_unreadable = config_unreadable;
+    app.seed = cli.seed.or(app.config.seed);
+    let notices: Vec<String> = warnings.iter().map(summary).collect();
+    app.menu.notice = (!notices.is_empty()).then(|| notices.join(" · "));
+    match cli.mode {
+        StartMode::Menu => {}
+        StartMode::VsAi => app.start_game(Opponent::Computer(app.config.difficulty)),
+        StartMode::TwoPlayer => app.start_game(Opponent::Human),
+        StartMode::Host => app.host_game(),
+        StartMode::Join => app.open_join_screen(false, true),
+        StartMode::Watch => app.open_join_screen(true, true),
+    }
+    let result = app.run(terminal);
+    if releases {
+        let _ = execute!(io::stdout(), PopKeyboardEnhancementFlags);
+    }
+    let _ = execute!(io::stdout(), DisableMouseCapture);
     ratatui::restore();
+
+    for warning in &warnings {
+        eprintln!("Warning: {}", summary(warning));
+    }
     result
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Starts reporting mouse events, making sure reporting stops again if the app panics.
+///
+/// A terminal that won't report them is played with the keyboard alone.
+fn enable_mouse_capture() {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+        hook(info);
+    }));
+    let _ = execute!(io::stdout(), EnableMouseCapture);
+}
+
+/// `error` squeezed onto one line: each context, then the gist of the root cause.
+fn summary(error: &color_eyre::Report) -> String {
+    error
+        .chain()
+        .map(|cause| {
+            let cause = cause.to_string();
+            // multi-line messages, like TOML's, end with the actual complaint
+            let gist = cause.lines().rfind(|line| !line.trim().is_empty());
+            gist.unwrap_or_default().trim().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(": ")
+}
+
+/// Asks the terminal to report key releases, so players can hold keys down together, making
+/// sure it stops again if the app panics.
+///
+/// Returns whether the terminal supports it.
+fn enable_key_releases() -> bool {
+    let enabled = matches!(supports_keyboard_enhancement(), Ok(true))
+        && execute!(
+            io::stdout(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )
+        .is_ok();
+    if enabled {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = execute!(io::stdout(), PopKeyboardEnhancementFlags);
+            hook(info);
+        }));
+    }
+    enabled
+}
+
+/// Upper bound on how often the screen is redrawn, in frames per second.
+const FRAME_RATE: f64 = 60.0;
+
+/// How long the countdown before a paused match resumes lasts, in seconds.
+const RESUME_COUNTDOWN: f64 = 3.0;
+
+/// Smallest terminal, in cells, that a match is shown in.
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 12;
+
+/// What the results screen says in a match someone else hosts.
+const HOST_REMATCH: &str = "Only the host can start a rematch";
+
+/// Longest stretch of real time the simulation will try to catch up on after a stall.
+const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 enum Mode {
+    #[default]
     Menu,
     Game,
+    Paused,
+    /// The results of a match that just ended.
+    Results,
+    Settings,
+    HighScores,
+    Help,
+    Bindings,
+    /// The list of saved replays.
+    Replays,
+    /// Watching a replay.
+    Replay,
+    /// Typing in the address of a match to join.
+    Join,
+    /// Waiting for a networked match to start.
+    Lobby,
+    /// A networked match stopped because the other end is gone.
+    Disconnected,
 }
 
-impl Default for Mode {
-    fn default() -> Self {
-        Self::Menu
+/// Who plays the right paddle; the left one is always a person at the keyboard, except when
+/// joining someone else's match.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Opponent {
+    #[default]
+    Human,
+    Computer(Difficulty),
+    /// Someone on another machine.
+    Remote,
+}
+
+impl fmt::Display for Opponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Human => f.write_str("Player 2"),
+            Self::Computer(difficulty) => write!(f, "Computer ({difficulty})"),
+            Self::Remote => f.write_str("Network player"),
+        }
+    }
+}
+
+impl FromStr for Opponent {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "Player 2" => return Ok(Self::Human),
+            "Network player" => return Ok(Self::Remote),
+            _ => {}
+        }
+        name.strip_prefix("Computer (")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(|| format!("unknown opponent {name:?}"))?
+            .parse()
+            .map(Self::Computer)
     }
 }
 
+/// Whether `area` is too small to show a playable field.
+fn too_small(area: Rect) -> bool {
+    area.width < MIN_WIDTH || area.height < MIN_HEIGHT
+}
+
+/// Where others can join a match hosted on `port`, for showing in the lobby.
+fn invitation(ip: Option<IpAddr>, port: u16) -> String {
+    match ip {
+        Some(ip) => format!("Others on your network can join at {ip}:{port}"),
+        None => format!("Others on your network can join on port {port}"),
+    }
+}
+
+/// A seed that differs from run to run.
+fn seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
 /// The main application which holds the state and logic of the application.
 #[derive(Debug, Default)]
 pub struct App {
     running: bool,
     mode: Mode,
+    menu: Menu,
+    pause_menu: PauseMenu,
+    /// Seconds left before the simulation picks up again after a pause.
+    countdown: Option<f64>,
+    /// The terminal area of the last rendered frame.
+    area: Rect,
+    game: GameState,
+    config: Config,
+    /// The settings as the file has them, without the command line's overrides.
+    saved_config: Config,
+    /// Whether the settings file is there but couldn't be read, in which case it is left alone
+    /// rather than replaced.
+    config_unreadable: bool,
+    settings: SettingsScreen,
+    opponent: Opponent,
+    /// Seed every match is played with, from the command line or settings file; without one
+    /// each match gets a new seed.
+    seed: Option<u64>,
+    /// The computer player, when [`App::opponent`] is one.
+    ai: Option<Ai>,
+    keyboard: Keyboard,
+    bindings: Bindings,
+    rebind: RebindScreen,
+    /// Where the mouse last asked a paddle to go, in arena units.
+    mouse: Option<(Side, f64)>,
+    /// The current match's inputs so far, saved once it ends.
+    recording: Option<Replay>,
+    results: ResultsScreen,
+    high_scores: HighScores,
+    replays: ReplayBrowser,
+    viewer: Option<ReplayViewer>,
+    /// Our end of a match played over the network.
+    network: Option<Network>,
+    join: JoinScreen,
+    /// What this end's paddle is doing, sent to the host when we joined its match.
+    net_input: PaddleInput,
+    /// Whether the host of the match we joined has paused it.
+    remote_paused: bool,
+    /// How many are watching the networked match.
+    spectators: usize,
+    /// Why the networked match stopped, shown over the field.
+    disconnected: String,
 }
 
 impl App {
     /// Construct a new instance of [`App`].
-    pub fn new() -> Self {
-        Self::default()
+    ///
+    /// `releases` says whether the terminal reports key releases.
+    pub fn new(releases: bool, config: Config, bindings: Bindings) -> Self {
+        let mut app = Self {
+            keyboard: Keyboard::new(releases),
+            bindings,
+            ..Self::default()
+        };
+        app.menu.difficulty = config.difficulty;
+        app.config = config;
+        app
     }
 
     /// Run the application's main loop.
+    ///
+    /// The simulation advances in fixed steps of one over the configured tick rate, fed by an
+    /// accumulator of elapsed real time, while rendering happens at most `FRAME_RATE` times per
+    /// second. Input is polled in between so the loop never blocks waiting for a key.
     pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        let frame = Duration::from_secs_f64(1.0 / FRAME_RATE);
+        let mut accumulator = Duration::ZERO;
+        let mut last_update = Instant::now();
+
         self.running = true;
         while self.running {
+            let frame_start = Instant::now();
             terminal.draw(|frame| self.render(frame))?;
-            self.handle_crossterm_events()?;
+
+            let deadline = frame_start + frame;
+            self.handle_crossterm_events(deadline)?;
+            self.poll_network();
+
+            let now = Instant::now();
+            accumulator += (now - last_update).min(MAX_FRAME_TIME);
+            last_update = now;
+            // replays repeat the exact time step, so don't pass the rounded `tick`
+            let dt = self.dt();
+            let tick = Duration::from_secs_f64(dt);
+            while accumulator >= tick {
+                self.tick(dt);
+                accumulator -= tick;
+            }
         }
         Ok(())
     }
 
+    /// Length of one simulation step: one over the configured tick rate, or over the one the
+    /// host picked in a rollback match.
+    fn dt(&self) -> f64 {
+        match &self.network {
+            Some(Network::Peer(peer)) if let Some(session) = peer.session() => session.dt(),
+            _ => 1.0 / f64::from(self.config.tick_rate),
+        }
+    }
+
+    /// Advances the simulation by one fixed step of `dt` seconds.
+    fn tick(&mut self, dt: f64) {
+        // a rollback match is played at both ends, so it carries on while either pauses
+        if self.mode == Mode::Paused && matches!(self.network, Some(Network::Peer(_))) {
+            self.advance_peer(PaddleInput::Idle);
+            return;
+        }
+        if self.mode == Mode::Replay
+            && let Some(viewer) = &mut self.viewer
+        {
+            viewer.update(dt);
+            self.game = viewer.game().clone();
+            return;
+        }
+        if self.mode != Mode::Game || too_small(self.area) {
+            return;
+        }
+        if let Some(countdown) = &mut self.countdown {
+            *countdown -= dt;
+            if *countdown > 0.0 {
+                return;
+            }
+            self.countdown = None;
+        }
+
+        let left = self.keyboard.paddle_input(
+            self.bindings.keys(Action::P1Up),
+            self.bindings.keys(Action::P1Down),
+        );
+        let right = self.keyboard.paddle_input(
+            self.bindings.keys(Action::P2Up),
+            self.bindings.keys(Action::P2Down),
+        );
+        self.keyboard.update(dt);
+        let mut inputs = if self.opponent == Opponent::Human {
+            Inputs { left, right }
+        } else {
+            // a lone player may steer with either set of keys
+            let local = if left == PaddleInput::Idle {
+                right
+            } else {
+                left
+            };
+            let other = match (&mut self.ai, &self.network) {
+                (Some(ai), _) => ai.input(&self.game, dt),
+                (None, Some(Network::Host(host))) => host.input(),
+                _ => PaddleInput::Idle,
+            };
+            match self.local_side() {
+                Side::Left => Inputs {
+                    left: local,
+                    right: other,
+                },
+                Side::Right => Inputs {
+                    left: other,
+                    right: local,
+                },
+            }
+        };
+        // the mouse steers until the keyboard takes over again
+        if let Some((side, y)) = self.mouse {
+            let input = match side {
+                Side::Left => &mut inputs.left,
+                Side::Right => &mut inputs.right,
+            };
+            if *input == PaddleInput::Idle {
+                *input = PaddleInput::Target(y);
+            } else {
+                self.mouse = None;
+            }
+        }
+        match self.network {
+            // the host runs the match we joined; all we do is tell it what our paddle is doing
+            Some(Network::Client(_)) => {
+                self.net_input = inputs.right;
+                return;
+            }
+            Some(Network::Peer(_)) => {
+                let local = match self.local_side() {
+                    Side::Left => inputs.left,
+                    Side::Right => inputs.right,
+                };
+                self.advance_peer(local);
+                return;
+            }
+            _ => {}
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.record(inputs);
+        }
+        self.game.step(inputs, dt);
+        if self.game.winner().is_some() {
+            self.save_match();
+        }
+    }
+
     /// Renders the user interface.
     fn render(&mut self, frame: &mut Frame) {
+        self.area = frame.area();
+        let field_shown = matches!(
+            self.mode,
+            Mode::Game | Mode::Paused | Mode::Replay | Mode::Disconnected
+        );
+        if field_shown && too_small(self.area) {
+            let text = format!(
+                "Terminal too small\n\n{}x{} is needed, this is {}x{}",
+                MIN_WIDTH, MIN_HEIGHT, self.area.width, self.area.height
+            );
+            Self::render_screen(frame, " Pong ", text);
+            return;
+        }
+
         match self.mode {
             Mode::Menu => {
-                let title = Line::from(" Pong Game \n").bold().blue().centered();
-                let text = "Esc, Ctrl-C or q to Quit\nEsc to go back to Menu\nEnter to start Game";
-
-                frame.render_widget(
-                    Paragraph::new(text)
-                        .block(Block::bordered().title(title))
-                        .centered(),
-                    frame.area(),
+                let hint = format!(
+                    "{}, {} to move · {}, {} difficulty · {} to select",
+                    self.key_names(Action::MenuUp),
+                    self.key_names(Action::MenuDown),
+                    self.key_names(Action::MenuLeft),
+                    self.key_names(Action::MenuRight),
+                    self.key_names(Action::Confirm)
                 );
+                self.menu.render(&hint, frame, frame.area());
             }
             Mode::Game => {
-                let block = Block::bordered();
-                let inner_area = block.inner(frame.area());
+                self.render_game(frame);
+                if let Some(countdown) = self.countdown {
+                    let popup = popup_area(frame.area(), 7, 3);
+                    frame.render_widget(Clear, popup);
+                    frame.render_widget(
+                        Paragraph::new(format!("{}", countdown.ceil()))
+                            .bold()
+                            .centered()
+                            .block(Block::bordered()),
+                        popup,
+                    );
+                } else if self.remote_paused {
+                    Self::render_popup(frame, "Paused by the host".to_string());
+                }
+                if let Some(Network::Peer(peer)) = &self.network
+                    && let Some(session) = peer.session()
+                {
+                    let area = frame.area();
+                    let bottom = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+                    let ahead = session.tick() - session.confirmed_tick();
+                    let status = format!(
+                        " {ahead} ticks guessed · {} rollbacks ",
+                        session.stats().rollbacks
+                    );
+                    frame.render_widget(Line::from(status).dim().centered(), bottom);
+                }
+            }
+            Mode::Paused => {
+                self.render_game(frame);
+                frame.render_widget(&self.pause_menu, frame.area());
+            }
+            Mode::Results => {
+                let hint = format!(
+                    "↑/↓ to move · {} to select · {} to go back to Menu",
+                    self.key_names(Action::Confirm),
+                    self.key_names(Action::Back)
+                );
+                self.results.render(&self.game, &hint, frame, frame.area());
+            }
+            Mode::Settings => {
+                let hint = format!(
+                    "↑/↓ to move · ←/→ to change · {} to go back to Menu",
+                    self.key_names(Action::Back)
+                );
+                self.settings
+                    .render(&self.config, &hint, frame, frame.area());
+            }
+            Mode::HighScores => {
+                let hint = format!("{} to go back to Menu", self.key_names(Action::Back));
+                self.high_scores.render(&hint, frame, frame.area());
+            }
+            Mode::Help => Self::render_screen(frame, " Help ", self.help()),
+            Mode::Bindings => self.rebind.render(&self.bindings, frame, frame.area()),
+            Mode::Replays => {
+                let hint = format!(
+                    "{} to watch · {} to go back to Menu",
+                    self.key_names(Action::Confirm),
+                    self.key_names(Action::Back)
+                );
+                self.replays.render(&hint, frame, frame.area());
+            }
+            Mode::Replay => {
+                self.render_game(frame);
+                if let Some(viewer) = &self.viewer {
+                    let area = frame.area();
+                    let bottom = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+                    frame.render_widget(Line::from(viewer.status()).centered(), bottom);
+                }
+            }
+            Mode::Join => self.join.render(frame, frame.area()),
+            Mode::Lobby => Self::render_screen(frame, " Network Game ", self.lobby()),
+            Mode::Disconnected => {
+                self.render_game(frame);
+                Self::render_popup(
+                    frame,
+                    format!(
+                        "{}\n\n{} to go back to Menu",
+                        self.disconnected,
+                        self.key_names(Action::Confirm)
+                    ),
+                );
+            }
+        }
+    }
+
+    /// What the lobby says while waiting for a networked match to start.
+    fn lobby(&self) -> String {
+        let status = match &self.network {
+            Some(Network::Host(host)) => format!(
+                "Hosting a match\n\n{}\n\nWaiting for another player…",
+                invitation(host.ip(), host.port())
+            ),
+            Some(Network::Peer(peer)) if peer.hosting() => format!(
+                "Hosting a rollback match\n\n{}\n\nWaiting for another player…",
+                invitation(peer.ip(), peer.port())
+            ),
+            Some(Network::Client(client)) if client.welcomed() => {
+                let joined = if client.watching() {
+                    "Watching"
+                } else {
+                    "Joined"
+                };
+                format!(
+                    "{joined} {}\n\nWaiting for the match to start…",
+                    self.join.address
+                )
+            }
+            _ => format!("Connecting to {}…", self.join.address),
+        };
+        format!(
+            "{status}\n\n{} to go back to Menu",
+            self.key_names(Action::Back)
+        )
+    }
+
+    /// Renders `text` in a small box over the middle of the screen.
+    fn render_popup(frame: &mut Frame, text: String) {
+        let width = text.lines().map(|line| line.chars().count()).max();
+        let width = width.unwrap_or_default() as u16 + 4;
+        let popup = popup_area(frame.area(), width, text.lines().count() as u16 + 2);
+        frame.render_widget(Clear, popup);
+        frame.render_widget(
+            Paragraph::new(text).centered().block(Block::bordered()),
+            popup,
+        );
+    }
+
+    /// Renders the field, paddles and ball of the current match.
+    fn render_game(&mut self, frame: &mut Frame) {
+        let palette = self.config.theme.palette();
+        let block = self.game_block();
+        let inner_area = block.inner(frame.area());
+
+        frame.render_widget(block, frame.area());
+        Self::center_line(frame, inner_area, Style::default().fg(palette.net));
+        let score = self.game.score();
+        let scoreboard = Scoreboard::new(score.left, score.right);
+        frame.render_widget(
+            scoreboard.style(Style::default().fg(palette.score)),
+            inner_area,
+        );
+
+        let mut pixels = PixelGrid::new(
+            self.config.resolution,
+            inner_area,
+            ARENA_WIDTH,
+            ARENA_HEIGHT,
+        )
+        .style(Style::default().fg(palette.foreground));
+        for side in [Side::Left, Side::Right] {
+            let paddle = self.game.paddle(side);
+            pixels.fill(paddle.x, paddle.y, PADDLE_WIDTH, paddle.height);
+        }
+        let ball = self.game.ball();
+        pixels.fill(ball.x, ball.y, BALL_SIZE, BALL_SIZE);
+        frame.render_widget(&pixels, inner_area);
+    }
+
+    /// The keys bound to `action`, for showing to the player.
+    fn key_names(&self, action: Action) -> String {
+        let names: Vec<String> = self
+            .bindings
+            .keys(action)
+            .iter()
+            .map(KeyBinding::to_string)
+            .collect();
+        match names.as_slice() {
+            [] => "(unbound)".to_string(),
+            names => names.join(" / "),
+        }
+    }
+
+    /// The controls listed on the help screen.
+    fn help(&self) -> String {
+        // two actions a line, so the list still fits a short terminal
+        let mut text: String = Action::ALL
+            .chunks(2)
+            .map(|pair| {
+                let pair: Vec<String> = pair
+                    .iter()
+                    .map(|&action| format!("{}: {}", action.description(), self.key_names(action)))
+                    .collect();
+                format!("{}\n", pair.join(" · "))
+            })
+            .collect();
+        text.push_str("Ctrl-C: Quit\n\n");
+        text.push_str("In a 1 player or network game, either paddle's keys move your paddle.\n");
+        text.push_str(
+            "Host Game and Join Game play over the local network; the host is on the left.\n",
+        );
+        text.push_str("Watch Game follows a hosted match without playing in it.\n");
+        text.push_str("Mouse: move over the field to steer a paddle, click to pick menu items\n");
+        text.push_str(&format!(
+            "\nKeys can be changed under Settings.\n\n{} to go back to Menu",
+            self.key_names(Action::Back)
+        ));
+        text
+    }
+
+    /// Renders a full-screen bordered panel of centered `text`.
+    fn render_screen(frame: &mut Frame, title: &'static str, text: String) {
+        let title = Line::from(title).bold().blue().centered();
+        frame.render_widget(
+            Paragraph::new(text)
+                .block(Block::bordered().title(title))
+                .centered(),
+            frame.area(),
+        );
+    }
 
-                frame.render_widget(block, frame.area());
-                Self::center_line(frame, inner_area);
+    /// Reads the crossterm events until `deadline` and updates the state of [`App`].
+    fn handle_crossterm_events(&mut self, deadline: Instant) -> Result<()> {
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if !event::poll(timeout)? {
+                return Ok(());
             }
+            match event::read()? {
+                Event::Key(key) => {
+                    self.keyboard.handle(key);
+                    // releases only matter for held keys, so don't handle them as commands
+                    if key.kind != KeyEventKind::Release {
+                        self.on_key_event(key);
+                    }
+                }
+                Event::Mouse(mouse) => self.on_mouse_event(mouse),
+                Event::Resize(width, height) => self.on_resize(width, height),
+                _ => {}
+            }
+            if !self.running {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Pauses the match, so nobody loses a point while the window is being dragged around.
+    ///
+    /// The arena is projected onto whatever size the terminal has on the next render.
+    fn on_resize(&mut self, width: u16, height: u16) {
+        self.area = Rect::new(0, 0, width, height);
+        if self.mode == Mode::Game {
+            self.pause();
         }
     }
 
-    /// Reads the crossterm events and updates the state of [`App`].
-    fn handle_crossterm_events(&mut self) -> Result<()> {
-        match event::read()? {
-            // it's important to check KeyEventKind::Press to avoid handling key release events
-            Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
-            Event::Mouse(_) => {}
-            Event::Resize(_, _) => {}
+    /// Handles the mouse events and updates the state of [`App`].
+    fn on_mouse_event(&mut self, mouse: MouseEvent) {
+        match (self.mode, mouse.kind) {
+            (Mode::Menu, MouseEventKind::Moved) => {
+                self.menu.select_at(self.area, mouse.row);
+            }
+            (Mode::Menu, MouseEventKind::Down(MouseButton::Left))
+                if self.menu.select_at(self.area, mouse.row) =>
+            {
+                self.confirm_menu_item();
+            }
+            (Mode::Game, MouseEventKind::Moved | MouseEventKind::Drag(_)) => {
+                let field = self.game_block().inner(self.area);
+                if !field.contains((mouse.column, mouse.row).into()) {
+                    return;
+                }
+                // with only one player here the mouse always steers their paddle, otherwise
+                // whichever half of the field it is over
+                let side = if self.opponent != Opponent::Human {
+                    self.local_side()
+                } else if mouse.column < field.x + field.width / 2 {
+                    Side::Left
+                } else {
+                    Side::Right
+                };
+                let y = (f64::from(mouse.row - field.y) + 0.5) / f64::from(field.height);
+                self.mouse = Some((side, y * ARENA_HEIGHT));
+            }
             _ => {}
         }
-        Ok(())
     }
 
     /// Handles the key events and updates the state of [`App`].
     fn on_key_event(&mut self, key: KeyEvent) {
-        match (key.modifiers, key.code) {
-            (_, KeyCode::Esc) => match self.mode {
-                Mode::Menu => self.quit(),
-                Mode::Game => self.mode = Mode::Menu,
+        if key.modifiers == KeyModifiers::CONTROL
+            && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('C'))
+        {
+            self.quit();
+            return;
+        }
+
+        match self.mode {
+            Mode::Menu => self.on_menu_key(key),
+            Mode::Game => self.on_game_key(key),
+            Mode::Paused => self.on_paused_key(key),
+            Mode::Results => self.on_results_key(key),
+            Mode::Settings => self.on_settings_key(key),
+            Mode::HighScores | Mode::Help => self.on_screen_key(key),
+            Mode::Bindings => self.on_bindings_key(key),
+            Mode::Replays => self.on_replays_key(key),
+            Mode::Replay => self.on_replay_key(key),
+            Mode::Join => self.on_join_key(key),
+            Mode::Lobby => self.on_lobby_key(key),
+            Mode::Disconnected => self.on_disconnected_key(key),
+        }
+    }
+
+    fn on_menu_key(&mut self, key: KeyEvent) {
+        use Action::*;
+        let actions = [MenuUp, MenuDown, MenuLeft, MenuRight, Confirm, Back, Quit];
+        match self.bindings.find(key, &actions) {
+            Some(MenuUp) => self.menu.previous(),
+            Some(MenuDown) => self.menu.next(),
+            Some(MenuLeft | MenuRight) => {
+                self.menu
+                    .cycle_difficulty(self.bindings.matches(MenuRight, key));
+                let difficulty = self.menu.difficulty;
+                self.save_setting(|config| config.difficulty = difficulty);
+            }
+            Some(Confirm) => self.confirm_menu_item(),
+            Some(Back | Quit) => self.quit(),
+            _ => {}
+        }
+    }
+
+    fn confirm_menu_item(&mut self) {
+        match self.menu.selected() {
+            MenuItem::OnePlayer => self.start_game(Opponent::Computer(self.menu.difficulty)),
+            MenuItem::TwoPlayers => self.start_game(Opponent::Human),
+            MenuItem::HostGame => self.host_game(),
+            MenuItem::JoinGame => self.open_join_screen(false, false),
+            MenuItem::WatchGame => self.open_join_screen(true, false),
+            MenuItem::Settings => {
+                self.settings = SettingsScreen::default();
+                self.mode = Mode::Settings;
+            }
+            MenuItem::HighScores => {
+                self.high_scores = HighScores::load();
+                self.mode = Mode::HighScores;
+            }
+            MenuItem::Replays => {
+                self.replays = ReplayBrowser::load();
+                self.mode = Mode::Replays;
+            }
+            MenuItem::Help => self.mode = Mode::Help,
+            MenuItem::Quit => self.quit(),
+        }
+    }
+
+    fn on_game_key(&mut self, key: KeyEvent) {
+        use Action::*;
+        match self.bindings.find(key, &[Pause, Quit, Resolution]) {
+            Some(Pause) => self.pause(),
+            Some(Quit) => self.quit(),
+            Some(Resolution) => self.config.resolution = self.config.resolution.next(),
+            _ => {}
+        }
+    }
+
+    fn on_paused_key(&mut self, key: KeyEvent) {
+        use Action::*;
+        match self
+            .bindings
+            .find(key, &[Pause, Back, MenuUp, MenuDown, Confirm, Quit])
+        {
+            Some(Pause | Back) => self.resume(),
+            Some(MenuUp) => self.pause_menu.previous(),
+            Some(MenuDown) => self.pause_menu.next(),
+            Some(Confirm) => match self.pause_menu.selected() {
+                PauseItem::Resume => self.resume(),
+                PauseItem::Restart => self.start_game(self.opponent),
+                PauseItem::QuitToMenu => self.leave_match(),
+            },
+            Some(Quit) => self.quit(),
+            _ => {}
+        }
+    }
+
+    /// Freezes the match and shows the pause popup.
+    fn pause(&mut self) {
+        self.pause_menu.reset();
+        self.mode = Mode::Paused;
+    }
+
+    /// Closes the pause popup and counts down before the match continues.
+    ///
+    /// A joined match carried on at the host all along, so there is nothing to count down.
+    fn resume(&mut self) {
+        if !self.pause_menu.remote {
+            self.countdown = Some(RESUME_COUNTDOWN);
+        }
+        self.mode = Mode::Game;
+    }
+
+    fn on_results_key(&mut self, key: KeyEvent) {
+        use Action::*;
+        match self
+            .bindings
+            .find(key, &[MenuUp, MenuDown, Confirm, Back, Quit])
+        {
+            Some(MenuUp) => self.results.previous(),
+            Some(MenuDown) => self.results.next(),
+            Some(Confirm) => match self.results.selected() {
+                ResultsItem::Rematch => self.start_game(self.opponent),
+                ResultsItem::Menu => self.leave_match(),
+                ResultsItem::SaveReplay => self.keep_replay(),
             },
+            Some(Back) => self.leave_match(),
+            Some(Quit) => self.quit(),
+            _ => {}
+        }
+    }
+
+    fn on_settings_key(&mut self, key: KeyEvent) {
+        use Action::*;
+        let actions = [MenuUp, MenuDown, MenuLeft, MenuRight, Confirm, Back, Quit];
+        match self.bindings.find(key, &actions) {
+            Some(MenuUp) => self.settings.previous(),
+            Some(MenuDown) => self.settings.next(),
+            Some(MenuLeft) => {
+                self.settings.selected().adjust(&mut self.config, false);
+            }
+            Some(MenuRight) => {
+                self.settings.selected().adjust(&mut self.config, true);
+            }
+            Some(Confirm) => {
+                if self.settings.selected() == SettingItem::KeyBindings {
+                    self.rebind = RebindScreen::default();
+                    self.mode = Mode::Bindings;
+                } else {
+                    self.settings.selected().adjust(&mut self.config, true);
+                }
+            }
+            Some(Back) => {
+                self.menu.difficulty = self.config.difficulty;
+                self.save_config();
+                self.mode = Mode::Menu;
+            }
+            Some(Quit) => self.quit(),
+            _ => {}
+        }
+    }
+
+    /// Writes the settings back to their file, complaining on the menu if that fails.
+    ///
+    /// Everything on the Settings screen is saved, command-line overrides included.
+    fn save_config(&mut self) {
+        self.saved_config = self.config.clone();
+        self.write_config();
+    }
+
+    /// Changes one setting, for this run and in the settings file, leaving the file's other
+    /// settings as they were.
+    fn save_setting(&mut self, change: impl Fn(&mut Config)) {
+        change(&mut self.config);
+        change(&mut self.saved_config);
+        self.write_config();
+    }
+
+    fn write_config(&mut self) {
+        if self.config_unreadable {
+            self.menu.notice =
+                Some("Settings not saved: fix or remove the unreadable settings file".to_string());
+            return;
+        }
+        self.menu.notice = self
+            .saved_config
+            .save()
+            .err()
+            .map(|error| format!("Settings not saved: {}", summary(&error)));
+    }
 
-            (_, KeyCode::Char('q')) => self.quit(),
+    /// Handles keys on the informational screens, which can only be left.
+    fn on_screen_key(&mut self, key: KeyEvent) {
+        use Action::*;
+        match self.bindings.find(key, &[Confirm, Back, Quit]) {
+            Some(Confirm | Back) => self.mode = Mode::Menu,
+            Some(Quit) => self.quit(),
+            _ => {}
+        }
+    }
+
+    /// Handles keys on the rebinding screen, whose own keys can't be rebound.
+    fn on_bindings_key(&mut self, key: KeyEvent) {
+        if self.rebind.capturing() {
+            self.rebind.set_capturing(false);
+            if key.code != KeyCode::Esc {
+                let action = self.rebind.selected();
+                self.bindings.set(action, KeyBinding::from_event(key));
+                self.save_bindings();
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.rebind.previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.rebind.next(),
+            KeyCode::Enter => self.rebind.set_capturing(true),
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.bindings.reset(self.rebind.selected());
+                self.save_bindings();
+            }
+            KeyCode::Esc => self.mode = Mode::Settings,
+            _ => {}
+        }
+    }
+
+    /// Writes the bindings back to their file, reporting how that went on the rebinding screen.
+    fn save_bindings(&mut self) {
+        self.rebind.status = Some(match self.bindings.save() {
+            Ok(()) => match Bindings::path() {
+                Ok(path) => format!("Saved to {}", path.display()),
+                Err(_) => "Saved".to_string(),
+            },
+            Err(error) => format!("Could not save: {error:#}"),
+        });
+    }
 
-            (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
+    fn on_replays_key(&mut self, key: KeyEvent) {
+        use Action::*;
+        match self
+            .bindings
+            .find(key, &[MenuUp, MenuDown, Confirm, Back, Quit])
+        {
+            Some(MenuUp) => self.replays.previous(),
+            Some(MenuDown) => self.replays.next(),
+            Some(Confirm) => {
+                let Some(entry) = self.replays.selected() else {
+                    return;
+                };
+                match replays::load(&entry.path) {
+                    Ok(replay) => {
+                        let viewer = ReplayViewer::new(replay);
+                        self.game = viewer.game().clone();
+                        self.viewer = Some(viewer);
+                        self.mode = Mode::Replay;
+                    }
+                    Err(error) => self.replays.status = Some(summary(&error)),
+                }
+            }
+            Some(Back) => self.mode = Mode::Menu,
+            Some(Quit) => self.quit(),
+            _ => {}
+        }
+    }
 
-            (_, KeyCode::Enter) => self.start_game(),
+    fn on_replay_key(&mut self, key: KeyEvent) {
+        use Action::*;
+        // Esc is both Back and Pause by default; here it leaves, like on every other screen
+        let actions = [
+            Back,
+            Pause,
+            SeekBack,
+            SeekForward,
+            StepBack,
+            StepForward,
+            Faster,
+            Slower,
+            Resolution,
+            Quit,
+        ];
+        let action = self.bindings.find(key, &actions);
+        let Some(viewer) = &mut self.viewer else {
+            return;
+        };
+        match action {
+            Some(Pause) => viewer.toggle_pause(),
+            Some(SeekBack) => viewer.seek(false),
+            Some(SeekForward) => viewer.seek(true),
+            Some(StepBack) => viewer.step(false),
+            Some(StepForward) => viewer.step(true),
+            Some(Faster) => viewer.faster(),
+            Some(Slower) => viewer.slower(),
+            Some(Resolution) => self.config.resolution = self.config.resolution.next(),
+            Some(Back) => {
+                self.viewer = None;
+                self.mode = Mode::Replays;
+            }
+            Some(Quit) => self.quit(),
             _ => {}
         }
+        if let Some(viewer) = &self.viewer {
+            self.game = viewer.game().clone();
+        }
+    }
+
+    /// Adds the match that just ended to the history and saves its replay, then shows the
+    /// results along with how that went.
+    fn save_match(&mut self) {
+        let mut problems = Vec::new();
+        let record = MatchRecord::new(&self.game, self.opponent);
+        if let Err(error) = History::append(record) {
+            problems.push(format!("Result not saved: {}", summary(&error)));
+        }
+        let replay = match self
+            .recording
+            .take()
+            .map(|recording| replays::save(&recording))
+        {
+            Some(Ok(path)) => Some(path),
+            Some(Err(error)) => {
+                problems.push(format!("Replay not saved: {}", summary(&error)));
+                None
+            }
+            None => None,
+        };
+        let status = if problems.is_empty() {
+            "Result and replay saved".to_string()
+        } else {
+            problems.join("\n")
+        };
+        self.show_results(replay, status);
+    }
+
+    /// Shows the results of the match that just ended, with `status` under them.
+    fn show_results(&mut self, replay: Option<PathBuf>, status: String) {
+        self.results = ResultsScreen::new(self.pause_menu.remote, replay, status);
+        self.mode = Mode::Results;
+    }
+
+    /// Keeps the replay of the match on the results screen from being deleted to make room
+    /// for newer ones.
+    fn keep_replay(&mut self) {
+        let Some(path) = &self.results.replay else {
+            return;
+        };
+        self.results.status = match replays::keep(path) {
+            Ok(kept) => {
+                self.results.replay = Some(kept);
+                "Replay kept; newer matches won't replace it".to_string()
+            }
+            Err(error) => format!("Replay not kept: {}", summary(&error)),
+        };
+    }
+
+    fn on_join_key(&mut self, key: KeyEvent) {
+        if self.join.edit(key) {
+            return;
+        }
+        match key.code {
+            KeyCode::Enter => self.join_game(),
+            KeyCode::Esc => self.mode = Mode::Menu,
+            _ => {}
+        }
+    }
+
+    fn on_lobby_key(&mut self, key: KeyEvent) {
+        use Action::*;
+        match self.bindings.find(key, &[Back, Quit]) {
+            Some(Back) => self.leave_match(),
+            Some(Quit) => self.quit(),
+            _ => {}
+        }
+    }
+
+    fn on_disconnected_key(&mut self, key: KeyEvent) {
+        use Action::*;
+        match self.bindings.find(key, &[Confirm, Back, Quit]) {
+            Some(Confirm | Back) => self.mode = Mode::Menu,
+            Some(Quit) => self.quit(),
+            _ => {}
+        }
+    }
+
+    /// Starts listening for someone to join a match, and waits in the lobby.
+    fn host_game(&mut self) {
+        let network = match self.config.netcode {
+            Netcode::Host => Host::listen(self.config.port).map(Network::Host),
+            Netcode::Rollback => Peer::host(
+                self.config.port,
+                self.seed.unwrap_or_else(seed),
+                self.config.game_settings(),
+                self.config.tick_rate,
+                self.config.rollback_settings(),
+            )
+            .map(|peer| Network::Peer(Box::new(peer))),
+        };
+        match network {
+            Ok(network) => {
+                self.network = Some(network);
+                self.mode = Mode::Lobby;
+            }
+            Err(error) => {
+                self.menu.notice = Some(format!("Could not host: {}", summary(&error)));
+                self.mode = Mode::Menu;
+            }
+        }
+    }
+
+    /// Shows the Join Game screen, or the Watch Game one if `watch` is set, with the last
+    /// address joined, joining it straight away if `now` is set.
+    fn open_join_screen(&mut self, watch: bool, now: bool) {
+        self.join = JoinScreen::new(self.config.join_address.clone(), watch);
+        self.mode = Mode::Join;
+        if now {
+            self.join_game();
+        }
+    }
+
+    /// Connects to the address on the Join Game screen and waits in the lobby for the host.
+    ///
+    /// Only matches the host runs can be watched, whatever the netcode setting.
+    fn join_game(&mut self) {
+        let address = self.join.address.trim().to_string();
+        let network = match self.config.netcode {
+            _ if self.join.watch => Client::watch(&address).map(Network::Client),
+            Netcode::Host => Client::join(&address).map(Network::Client),
+            Netcode::Rollback => Peer::join(&address, self.config.rollback_settings())
+                .map(|peer| Network::Peer(Box::new(peer))),
+        };
+        match network {
+            Ok(network) => {
+                self.network = Some(network);
+                self.mode = Mode::Lobby;
+                if self.config.join_address != address {
+                    self.save_setting(|config| config.join_address = address.clone());
+                }
+            }
+            Err(error) => self.join.status = Some(summary(&error)),
+        }
+    }
+
+    /// Exchanges messages with the other end of a networked match, if one is being played.
+    fn poll_network(&mut self) {
+        if let Err(error) = self.exchange_messages() {
+            // spectators may still be there to say goodbye to
+            if let Some(network) = self.network.take() {
+                network.leave();
+            }
+            self.disconnected = summary(&error);
+            if self.mode == Mode::Lobby {
+                self.menu.notice = Some(self.disconnected.clone());
+                self.mode = Mode::Menu;
+            } else {
+                self.mode = Mode::Disconnected;
+            }
+        }
+    }
+
+    fn exchange_messages(&mut self) -> Result<()> {
+        match &mut self.network {
+            Some(Network::Host(host)) => {
+                let joined = host.poll()?;
+                self.spectators = host.spectators();
+                if joined {
+                    self.start_game(Opponent::Remote);
+                }
+                if !matches!(self.mode, Mode::Game | Mode::Paused | Mode::Results) {
+                    return Ok(());
+                }
+                let snapshot = Snapshot {
+                    game: self.game.clone(),
+                    paused: self.mode == Mode::Paused,
+                    countdown: self.countdown,
+                    spectators: self.spectators as u32,
+                };
+                if let Some(Network::Host(host)) = &mut self.network {
+                    host.send(&Message::Snapshot(Box::new(snapshot)))?;
+                }
+            }
+            Some(Network::Client(client)) => {
+                let input = if self.mode == Mode::Game && !client.watching() {
+                    self.net_input
+                } else {
+                    PaddleInput::Idle
+                };
+                client.send_input(input)?;
+                if let Some(snapshot) = client.poll()? {
+                    self.apply_snapshot(snapshot);
+                }
+            }
+            Some(Network::Peer(peer)) => {
+                let started = peer.poll()?;
+                if started {
+                    self.start_peer_match();
+                }
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Starts showing a rollback match both peers have just agreed on.
+    fn start_peer_match(&mut self) {
+        let Some(Network::Peer(peer)) = &self.network else {
+            return;
+        };
+        let Some(session) = peer.session() else {
+            return;
+        };
+        self.game = session.game().clone();
+        self.keyboard.clear();
+        self.mouse = None;
+        self.opponent = Opponent::Remote;
+        self.ai = None;
+        self.recording = None;
+        self.countdown = None;
+        self.remote_paused = false;
+        // neither peer can stop or restart a match the other is playing too
+        self.pause_menu.remote = true;
+        self.mode = Mode::Game;
+    }
+
+    /// Plays the next tick of a rollback match with the local player's `input`, ending the
+    /// match once both peers' inputs agree on a winner.
+    fn advance_peer(&mut self, input: PaddleInput) {
+        let Some(Network::Peer(peer)) = &mut self.network else {
+            return;
+        };
+        let hosting = peer.hosting();
+        let Some(session) = peer.session_mut() else {
+            return;
+        };
+        session.advance(input);
+        if session.confirmed().winner().is_none() {
+            self.game = session.game().clone();
+            return;
+        }
+        self.game = session.confirmed().clone();
+        // like the host of any other network match, only the hosting peer keeps the result
+        if hosting {
+            let mut recording = session.replay().clone();
+            recording.left = "Player 1".to_string();
+            recording.right = Opponent::Remote.to_string();
+            self.recording = Some(recording);
+            self.save_match();
+        } else {
+            self.show_results(None, String::new());
+        }
+    }
+
+    /// Shows the state of the match we joined, as the host last sent it.
+    fn apply_snapshot(&mut self, snapshot: Snapshot) {
+        self.game = snapshot.game;
+        self.remote_paused = snapshot.paused;
+        self.countdown = snapshot.countdown;
+        self.spectators = snapshot.spectators as usize;
+        let over = self.game.winner().is_some();
+        match self.mode {
+            Mode::Lobby => {
+                self.keyboard.clear();
+                self.mouse = None;
+                self.net_input = PaddleInput::Idle;
+                self.opponent = Opponent::Remote;
+                self.ai = None;
+                self.recording = None;
+                self.pause_menu.remote = true;
+                self.mode = Mode::Game;
+                if over {
+                    self.show_results(None, HOST_REMATCH.to_string());
+                }
+            }
+            Mode::Game if over => self.show_results(None, HOST_REMATCH.to_string()),
+            Mode::Results if !over => self.mode = Mode::Game,
+            _ => {}
+        }
+    }
+
+    /// Leaves the current match for the menu, saying goodbye if it is played over the network.
+    fn leave_match(&mut self) {
+        if let Some(network) = self.network.take() {
+            network.leave();
+        }
+        self.mode = Mode::Menu;
+    }
+
+    /// Which paddle the person at this keyboard plays when they are on their own.
+    fn local_side(&self) -> Side {
+        match &self.network {
+            Some(Network::Client(_)) => Side::Right,
+            Some(Network::Peer(peer)) if !peer.hosting() => Side::Right,
+            _ => Side::Left,
+        }
     }
 
     fn quit(&mut self) {
+        if let Some(network) = self.network.take() {
+            network.leave();
+        }
         self.running = false;
     }
 
-    fn start_game(&mut self) {
+    fn start_game(&mut self, opponent: Opponent) {
+        let seed = self.seed.unwrap_or_else(seed);
+        let settings = self.config.game_settings();
+        self.game = GameState::new(settings, seed);
+        let mut recording = Replay::new(seed, settings, self.config.tick_rate);
+        recording.left = "Player 1".to_string();
+        recording.right = opponent.to_string();
+        self.recording = Some(recording);
+        self.keyboard.clear();
+        self.mouse = None;
+        self.opponent = opponent;
+        self.countdown = None;
+        self.pause_menu.remote = false;
+        self.remote_paused = false;
+        self.ai = match opponent {
+            Opponent::Human | Opponent::Remote => None,
+            Opponent::Computer(difficulty) => Some(Ai::new(
+                Side::Right,
+                difficulty.settings(),
+                self.game.side_rng(Side::Right),
+            )),
+        };
         self.mode = Mode::Game;
     }
 
-    fn center_line(frame: &mut Frame, area: Rect) {
+    /// The block framing the playing field, with the score above the net and, in a networked
+    /// match, how many are watching it.
+    fn game_block(&self) -> Block<'static> {
+        let score = self.game.score();
+        let score = format!(" {}   {} ", score.left, score.right);
+        let mut block = Block::bordered()
+            .border_style(Style::default().fg(self.config.theme.palette().border))
+            .title(Line::from(score).bold().centered());
+        if let Some(Network::Client(client)) = &self.network
+            && client.watching()
+        {
+            block = block.title(Line::from(" Watching ").left_aligned());
+        }
+        if matches!(self.network, Some(Network::Host(_) | Network::Client(_)))
+            && self.spectators > 0
+        {
+            let watching = format!(" {} watching ", self.spectators);
+            block = block.title(Line::from(watching).right_aligned());
+        }
+        block
+    }
+
+    fn center_line(frame: &mut Frame, area: Rect, style: Style) {
         if area.height < 1 || area.width < 1 {
             return;
         }
@@ -128,7 +1459,7 @@ impl App {
         }
 
         let text = lines.join("\n");
-        let p = Paragraph::new(text).style(Style::default());
+        let p = Paragraph::new(text).style(style);
         frame.render_widget(p, area);
     }
 }
@@ -0,0 +1,142 @@
+use pong_core::Difficulty;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Block, Paragraph},
+};
+
+use crate::settings::cycle;
+
+/// An entry of the main menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    OnePlayer,
+    TwoPlayers,
+    HostGame,
+    JoinGame,
+    WatchGame,
+    Settings,
+    HighScores,
+    Replays,
+    Help,
+    Quit,
+}
+
+impl MenuItem {
+    pub const ALL: [Self; 10] = [
+        Self::OnePlayer,
+        Self::TwoPlayers,
+        Self::HostGame,
+        Self::JoinGame,
+        Self::WatchGame,
+        Self::Settings,
+        Self::HighScores,
+        Self::Replays,
+        Self::Help,
+        Self::Quit,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::OnePlayer => "1 Player",
+            Self::TwoPlayers => "2 Players",
+            Self::HostGame => "Host Game",
+            Self::JoinGame => "Join Game",
+            Self::WatchGame => "Watch Game",
+            Self::Settings => "Settings",
+            Self::HighScores => "High Scores",
+            Self::Replays => "Replays",
+            Self::Help => "Help",
+            Self::Quit => "Quit",
+        }
+    }
+}
+
+/// The main menu: a list of [`MenuItem`]s with one of them selected.
+#[derive(Debug, Default)]
+pub struct Menu {
+    selected: usize,
+    /// The strength of the computer opponent for [`MenuItem::OnePlayer`].
+    pub difficulty: Difficulty,
+    /// Something the player should know about, shown above the hint line.
+    pub notice: Option<String>,
+}
+
+impl Menu {
+    pub fn selected(&self) -> MenuItem {
+        MenuItem::ALL[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % MenuItem::ALL.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = (self.selected + MenuItem::ALL.len() - 1) % MenuItem::ALL.len();
+    }
+
+    /// Selects the item drawn on `row` of the screen, if any, when the menu fills `area`.
+    ///
+    /// Returns whether an item was hit.
+    pub fn select_at(&mut self, area: Rect, row: u16) -> bool {
+        let (items, _, _) = layout(area);
+        if !(items.top()..items.bottom()).contains(&row) {
+            return false;
+        }
+        self.selected = usize::from(row - items.y);
+        true
+    }
+
+    /// Steps the difficulty forward or backward, wrapping around at either end.
+    pub fn cycle_difficulty(&mut self, forward: bool) {
+        self.difficulty = cycle(&Difficulty::ALL, self.difficulty, forward);
+    }
+
+    fn line(&self, index: usize, item: MenuItem) -> Line<'static> {
+        let label = match item {
+            MenuItem::OnePlayer => format!("{}  ‹ {} ›", item.label(), self.difficulty),
+            _ => item.label().to_string(),
+        };
+        if index == self.selected {
+            Line::from(format!("▶ {label} ◀")).bold().reversed()
+        } else {
+            Line::from(label)
+        }
+    }
+
+    pub fn render(&self, hint: &str, frame: &mut Frame, area: Rect) {
+        let title = Line::from(" Pong Game ").bold().blue().centered();
+        frame.render_widget(Block::bordered().title(title), area);
+        let (items, notice, footer) = layout(area);
+
+        let lines: Vec<Line> = MenuItem::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, &item)| self.line(index, item).centered())
+            .collect();
+        frame.render_widget(Paragraph::new(lines), items);
+
+        if let Some(text) = &self.notice {
+            frame.render_widget(Line::from(text.as_str()).red().centered(), notice);
+        }
+
+        frame.render_widget(Line::from(hint).dim().centered(), footer);
+    }
+}
+
+/// Where the items, the notice and the hint line go when the menu fills `area`.
+fn layout(area: Rect) -> (Rect, Rect, Rect) {
+    let inner = Block::bordered().inner(area);
+    let [body, notice, footer] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(inner);
+    let [items] = Layout::vertical([Constraint::Length(MenuItem::ALL.len() as u16)])
+        .flex(Flex::Center)
+        .areas(body);
+    (items, notice, footer)
+}
@@ -0,0 +1,718 @@
+//! Matches over the local network, played one of two ways (see [`Netcode`]).
+//!
+//! With [`Netcode::Host`] the host runs the simulation and streams [`Snapshot`]s to the client,
+//! which sends back its paddle input; both ends talk [`Message`]s over TCP. With
+//! [`Netcode::Rollback`] both peers run it and send each other their inputs as [`Datagram`]s
+//! over UDP, see [`Rollback`].
+
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    net::{IpAddr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail, ensure, eyre},
+};
+use pong_core::{
+    Datagram, GameSettings, Link, Message, PROTOCOL_VERSION, PaddleInput, Rollback,
+    RollbackSettings, Side, Snapshot,
+};
+use serde::{Deserialize, Serialize};
+
+/// Port a match is hosted on unless the settings say otherwise.
+pub const DEFAULT_PORT: u16 = 4000;
+
+/// How long joining waits for the host to pick up.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long the other end may stay silent before it counts as gone; both ends send every tick.
+const SILENCE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most unsent bytes allowed to pile up before the other end counts as gone.
+const MAX_BACKLOG: usize = 64 * 1024;
+
+/// Largest datagram read; anything longer isn't ours.
+const MAX_DATAGRAM_LEN: usize = 2048;
+
+/// Most connections allowed to wait at once before saying whether they play or watch.
+const MAX_PENDING: usize = 8;
+
+/// Most spectators a hosted match lets in.
+const MAX_SPECTATORS: usize = 32;
+
+/// Times a goodbye is sent over UDP, hoping one of them arrives.
+const BYE_REPEATS: usize = 3;
+
+/// How the two ends of a network match are kept in step.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Netcode {
+    /// The host runs the match and sends the client what happens, over TCP. Simple, but the
+    /// joining player feels the round trip on every key press.
+    #[default]
+    Host,
+    /// Both ends run the match and only send each other their inputs, over UDP, guessing the
+    /// other's and correcting when the guess was wrong.
+    Rollback,
+}
+
+impl Netcode {
+    pub const ALL: [Self; 2] = [Self::Host, Self::Rollback];
+}
+
+impl fmt::Display for Netcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Host => "Host runs the match",
+            Self::Rollback => "Rollback",
+        })
+    }
+}
+
+impl FromStr for Netcode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "host" => Ok(Self::Host),
+            "rollback" => Ok(Self::Rollback),
+            _ => Err(format!("unknown netcode {name:?}")),
+        }
+    }
+}
+
+/// A TCP connection carrying [`Message`]s, which never blocks.
+#[derive(Debug)]
+struct Connection {
+    stream: TcpStream,
+    /// Bytes received but not yet making up a whole message.
+    incoming: Vec<u8>,
+    /// Bytes the socket wasn't ready to take yet.
+    outgoing: Vec<u8>,
+    last_heard: Instant,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+            last_heard: Instant::now(),
+        })
+    }
+
+    fn send(&mut self, message: &Message) -> Result<()> {
+        self.outgoing.extend(message.encode());
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => bail!("the connection was closed"),
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error).wrap_err("sending"),
+            }
+        }
+        ensure!(
+            self.outgoing.len() <= MAX_BACKLOG,
+            "the other end stopped reading"
+        );
+        Ok(())
+    }
+
+    /// The messages that have arrived since the last call.
+    ///
+    /// A connection closed right after a [`Message::Bye`] or [`Message::Reject`] still hands
+    /// it over.
+    fn receive(&mut self) -> Result<Vec<Message>> {
+        let mut buffer = [0; 4096];
+        let mut closed = false;
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    closed = true;
+                    break;
+                }
+                Ok(read) => {
+                    self.incoming.extend_from_slice(&buffer[..read]);
+                    self.last_heard = Instant::now();
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error).wrap_err("receiving"),
+            }
+        }
+        ensure!(
+            closed || self.last_heard.elapsed() < SILENCE_TIMEOUT,
+            "nothing heard for {} seconds",
+            SILENCE_TIMEOUT.as_secs()
+        );
+
+        let mut messages = Vec::new();
+        let mut start = 0;
+        while let Some((message, len)) =
+            Message::decode(&self.incoming[start..]).wrap_err("reading a message")?
+        {
+            messages.push(message);
+            start += len;
+        }
+        self.incoming.drain(..start);
+        let farewell = |message: &Message| matches!(message, Message::Bye | Message::Reject(_));
+        ensure!(
+            !closed || messages.iter().any(farewell),
+            "the connection was closed"
+        );
+        Ok(messages)
+    }
+}
+
+/// Hosting a match: waits for a client to join, then plays against it while any number of
+/// spectators watch.
+#[derive(Debug)]
+pub struct Host {
+    listener: TcpListener,
+    /// This machine's address on the local network, if it could be told.
+    ip: Option<IpAddr>,
+    /// Connections that haven't said yet whether they play or watch.
+    pending: Vec<Connection>,
+    /// The client, once it has said hello and been welcomed.
+    client: Option<Connection>,
+    spectators: Vec<Connection>,
+    /// The client's latest paddle input.
+    input: PaddleInput,
+}
+
+impl Host {
+    /// Starts listening for a client on `port` of every interface.
+    pub fn listen(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .wrap_err_with(|| format!("listening on port {port}"))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            ip: local_address(),
+            pending: Vec::new(),
+            client: None,
+            spectators: Vec::new(),
+            input: PaddleInput::Idle,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.listener
+            .local_addr()
+            .map_or(0, |address| address.port())
+    }
+
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.ip
+    }
+
+    /// The client's paddle input, or idle before anyone has joined.
+    pub fn input(&self) -> PaddleInput {
+        self.input
+    }
+
+    /// How many are watching the match.
+    pub fn spectators(&self) -> usize {
+        self.spectators.len()
+    }
+
+    /// Takes in new connections and reads what the client and spectators sent.
+    ///
+    /// Returns `Ok(true)` when a client has just joined, and an error once a joined client is
+    /// gone. A connection that goes wrong before joining, or a spectator's, is dropped quietly.
+    pub fn poll(&mut self) -> Result<bool> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) if self.pending.len() >= MAX_PENDING => {
+                    turn_away(stream, "too many are joining at once")
+                }
+                Ok((stream, _)) => self.pending.extend(Connection::new(stream).ok()),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(_) => break,
+            }
+        }
+
+        let mut joined = false;
+        for mut connection in std::mem::take(&mut self.pending) {
+            let Ok(messages) = connection.receive() else {
+                continue;
+            };
+            let Some(hello) = messages
+                .into_iter()
+                .find(|message| matches!(message, Message::Hello { .. } | Message::Watch { .. }))
+            else {
+                self.pending.push(connection);
+                continue;
+            };
+            let refusal = match hello {
+                Message::Hello { version } | Message::Watch { version }
+                    if version != PROTOCOL_VERSION =>
+                {
+                    Some(format!(
+                        "the host speaks protocol version {PROTOCOL_VERSION}"
+                    ))
+                }
+                Message::Hello { .. } if self.client.is_some() => {
+                    Some("the match is full".to_string())
+                }
+                Message::Watch { .. } if self.spectators.len() >= MAX_SPECTATORS => {
+                    Some("too many are watching".to_string())
+                }
+                _ => None,
+            };
+            if let Some(reason) = refusal {
+                let _ = connection.send(&Message::Reject(reason));
+                continue;
+            }
+            if connection.send(&Message::Welcome).is_err() {
+                continue;
+            }
+            if matches!(hello, Message::Hello { .. }) {
+                self.client = Some(connection);
+                joined = true;
+            } else {
+                self.spectators.push(connection);
+            }
+        }
+
+        // spectators have nothing to say but goodbye
+        self.spectators.retain_mut(|spectator| {
+            spectator
+                .receive()
+                .is_ok_and(|messages| !messages.contains(&Message::Bye))
+        });
+
+        let Some(client) = &mut self.client else {
+            return Ok(joined);
+        };
+        let messages = match client.receive() {
+            Ok(messages) => messages,
+            Err(error) => return self.drop_client(error.wrap_err("Lost the other player")),
+        };
+        for message in messages {
+            match message {
+                Message::Input(input) => self.input = input,
+                Message::Bye => return self.drop_client(eyre!("The other player left")),
+                _ => {}
+            }
+        }
+        Ok(joined)
+    }
+
+    /// Sends `message` to the client and spectators, if any have joined.
+    pub fn send(&mut self, message: &Message) -> Result<()> {
+        self.spectators
+            .retain_mut(|spectator| spectator.send(message).is_ok());
+        let Some(client) = &mut self.client else {
+            return Ok(());
+        };
+        if let Err(error) = client.send(message) {
+            self.drop_client(error.wrap_err("Lost the other player"))?;
+        }
+        Ok(())
+    }
+
+    /// Forgets the client, passing on `error` if there was one.
+    fn drop_client(&mut self, error: color_eyre::Report) -> Result<bool> {
+        self.input = PaddleInput::Idle;
+        match self.client.take() {
+            Some(_) => Err(error),
+            None => Ok(false),
+        }
+    }
+}
+
+/// Tells a connection why it can't join, without waiting to hear from it.
+fn turn_away(stream: TcpStream, reason: &str) {
+    if let Ok(mut connection) = Connection::new(stream) {
+        let _ = connection.send(&Message::Reject(reason.to_string()));
+    }
+}
+
+/// Playing in or watching a match someone else hosts.
+#[derive(Debug)]
+pub struct Client {
+    connection: Connection,
+    /// Whether we only watch the match.
+    watching: bool,
+    /// Whether the host has let us in.
+    welcomed: bool,
+}
+
+impl Client {
+    /// Connects to the host at `address`, a `host:port` pair, and says hello.
+    pub fn join(address: &str) -> Result<Self> {
+        Self::connect(address, false)
+    }
+
+    /// Connects to the host at `address` to watch its match.
+    pub fn watch(address: &str) -> Result<Self> {
+        Self::connect(address, true)
+    }
+
+    fn connect(address: &str, watching: bool) -> Result<Self> {
+        let addresses: Vec<SocketAddr> = address
+            .to_socket_addrs()
+            .wrap_err_with(|| format!("looking up {address:?}"))?
+            .collect();
+        let mut last_error = eyre!("{address:?} has no addresses");
+        for socket_address in addresses {
+            match TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT) {
+                Ok(stream) => {
+                    let mut client = Self {
+                        connection: Connection::new(stream)?,
+                        watching,
+                        welcomed: false,
+                    };
+                    let hello = if watching {
+                        Message::watch()
+                    } else {
+                        Message::hello()
+                    };
+                    client.connection.send(&hello)?;
+                    return Ok(client);
+                }
+                Err(error) => {
+                    last_error = eyre!(error).wrap_err(format!("connecting to {socket_address}"));
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    pub fn watching(&self) -> bool {
+        self.watching
+    }
+
+    /// Whether the host has accepted us into the match.
+    pub fn welcomed(&self) -> bool {
+        self.welcomed
+    }
+
+    /// Reads what the host sent, returning the newest snapshot if any arrived.
+    pub fn poll(&mut self) -> Result<Option<Snapshot>> {
+        let messages = self
+            .connection
+            .receive()
+            .wrap_err("Lost the connection to the host")?;
+        let mut latest = None;
+        for message in messages {
+            match message {
+                Message::Welcome => self.welcomed = true,
+                Message::Reject(reason) => bail!("The host turned us away: {reason}"),
+                Message::Snapshot(snapshot) if self.welcomed => latest = Some(*snapshot),
+                Message::Bye => bail!("The host left"),
+                _ => {}
+            }
+        }
+        Ok(latest)
+    }
+
+    /// Tells the host what our paddle is doing; a spectator sends idle, so the host knows it is
+    /// still there.
+    pub fn send_input(&mut self, input: PaddleInput) -> Result<()> {
+        self.connection
+            .send(&Message::Input(input))
+            .wrap_err("Lost the connection to the host")
+    }
+}
+
+/// A UDP socket sending to and hearing from one other peer.
+#[derive(Debug)]
+struct UdpLink {
+    socket: UdpSocket,
+    peer: SocketAddr,
+}
+
+impl Link for UdpLink {
+    fn send(&mut self, datagram: &[u8]) -> io::Result<()> {
+        match self.socket.send_to(datagram, self.peer) {
+            Err(error) if error.kind() != io::ErrorKind::WouldBlock => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    fn receive(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut buffer = [0; MAX_DATAGRAM_LEN];
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((len, from)) if from == self.peer => return Ok(Some(buffer[..len].to_vec())),
+                Ok(_) => {}
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+/// How a rollback match is set up, decided by the hosting peer.
+#[derive(Debug, Clone, Copy)]
+struct Setup {
+    seed: u64,
+    settings: GameSettings,
+    tick_rate: u32,
+}
+
+impl Setup {
+    fn datagram(&self) -> Datagram {
+        Datagram::Start {
+            seed: self.seed,
+            settings: self.settings,
+            tick_rate: self.tick_rate,
+        }
+    }
+}
+
+/// Our end of a rollback match: hosting or joining one, then playing it.
+#[derive(Debug)]
+pub struct Peer {
+    socket: UdpSocket,
+    hosting: bool,
+    /// The other peer, once known.
+    link: Option<UdpLink>,
+    /// The match set-up; the hosting peer knows it from the start, the joining one once told.
+    setup: Option<Setup>,
+    rollback: RollbackSettings,
+    /// The match, once both peers know how it is set up.
+    session: Option<Rollback>,
+    ip: Option<IpAddr>,
+    last_heard: Instant,
+}
+
+impl Peer {
+    /// Waits on `port` for someone to join a match set up as given.
+    pub fn host(
+        port: u16,
+        seed: u64,
+        settings: GameSettings,
+        tick_rate: u32,
+        rollback: RollbackSettings,
+    ) -> Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))
+            .wrap_err_with(|| format!("listening on UDP port {port}"))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            hosting: true,
+            link: None,
+            setup: Some(Setup {
+                seed,
+                settings,
+                tick_rate,
+            }),
+            rollback,
+            session: None,
+            ip: local_address(),
+            last_heard: Instant::now(),
+        })
+    }
+
+    /// Asks the peer at `address`, a `host:port` pair, to let us join its match.
+    pub fn join(address: &str, rollback: RollbackSettings) -> Result<Self> {
+        let peer = address
+            .to_socket_addrs()
+            .wrap_err_with(|| format!("looking up {address:?}"))?
+            .next()
+            .ok_or_else(|| eyre!("{address:?} has no addresses"))?;
+        let unspecified: IpAddr = if peer.is_ipv4() {
+            [0, 0, 0, 0].into()
+        } else {
+            [0u16; 8].into()
+        };
+        let socket = UdpSocket::bind((unspecified, 0)).wrap_err("opening a UDP socket")?;
+        socket.set_nonblocking(true)?;
+        let link = UdpLink {
+            socket: socket.try_clone()?,
+            peer,
+        };
+        let mut joining = Self {
+            socket,
+            hosting: false,
+            link: Some(link),
+            setup: None,
+            rollback,
+            session: None,
+            ip: None,
+            last_heard: Instant::now(),
+        };
+        joining.send(&Datagram::hello())?;
+        Ok(joining)
+    }
+
+    /// Whether this end is hosting the match.
+    pub fn hosting(&self) -> bool {
+        self.hosting
+    }
+
+    pub fn port(&self) -> u16 {
+        self.socket.local_addr().map_or(0, |address| address.port())
+    }
+
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.ip
+    }
+
+    /// The match, once it has started.
+    pub fn session(&self) -> Option<&Rollback> {
+        self.session.as_ref()
+    }
+
+    pub fn session_mut(&mut self) -> Option<&mut Rollback> {
+        self.session.as_mut()
+    }
+
+    /// Sends what the other peer is waiting for and takes in what it sent.
+    ///
+    /// Returns `Ok(true)` when the match has just started, and an error once the other peer is
+    /// gone.
+    pub fn poll(&mut self) -> Result<bool> {
+        if self.session.is_none() {
+            return self.set_up();
+        }
+        let (Some(link), Some(session), Some(setup)) =
+            (&mut self.link, &mut self.session, &self.setup)
+        else {
+            return Ok(false);
+        };
+        // until the joining peer's inputs arrive it may not have heard how the match is set up
+        if self.hosting && session.remote_tick() == 0 {
+            let _ = link.send(&setup.datagram().encode());
+        }
+        let exchange = session.exchange(link).wrap_err("Lost the other player")?;
+        if exchange.bye {
+            bail!("The other player left");
+        }
+        if exchange.heard {
+            self.last_heard = Instant::now();
+        }
+        ensure!(
+            self.last_heard.elapsed() < SILENCE_TIMEOUT,
+            "Lost the other player: nothing heard for {} seconds",
+            SILENCE_TIMEOUT.as_secs()
+        );
+        Ok(false)
+    }
+
+    /// Handles the datagrams that come before the match starts.
+    fn set_up(&mut self) -> Result<bool> {
+        let mut buffer = [0; MAX_DATAGRAM_LEN];
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                // e.g. the host's port refusing us, which its silence will report
+                Err(_) => break,
+            };
+            let Ok(datagram) = Datagram::decode(&buffer[..len]) else {
+                continue;
+            };
+            match (&self.link, datagram) {
+                // hosting, and someone wants in
+                (None, Datagram::Hello { version }) => {
+                    if version != PROTOCOL_VERSION {
+                        let reason = format!("the host speaks protocol version {PROTOCOL_VERSION}");
+                        let _ = self
+                            .socket
+                            .send_to(&Datagram::Reject(reason).encode(), from);
+                        continue;
+                    }
+                    self.link = Some(UdpLink {
+                        socket: self.socket.try_clone()?,
+                        peer: from,
+                    });
+                    return Ok(self.start(Side::Left));
+                }
+                // joining, and the host answered
+                (
+                    Some(link),
+                    Datagram::Start {
+                        seed,
+                        settings,
+                        tick_rate,
+                    },
+                ) if link.peer == from => {
+                    self.setup = Some(Setup {
+                        seed,
+                        settings,
+                        tick_rate,
+                    });
+                    return Ok(self.start(Side::Right));
+                }
+                (Some(link), Datagram::Reject(reason)) if link.peer == from => {
+                    bail!("The host turned us away: {reason}")
+                }
+                _ => {}
+            }
+        }
+
+        if self.link.is_some() {
+            ensure!(
+                self.last_heard.elapsed() < SILENCE_TIMEOUT,
+                "No answer from the host; is it hosting a rollback match?"
+            );
+            self.send(&Datagram::hello())?;
+        }
+        Ok(false)
+    }
+
+    /// Starts the match, playing `side`.
+    fn start(&mut self, side: Side) -> bool {
+        let Some(setup) = self.setup else {
+            return false;
+        };
+        self.session = Some(Rollback::new(
+            setup.settings,
+            setup.seed,
+            setup.tick_rate,
+            side,
+            self.rollback,
+        ));
+        self.last_heard = Instant::now();
+        true
+    }
+
+    fn send(&mut self, datagram: &Datagram) -> Result<()> {
+        if let Some(link) = &mut self.link {
+            link.send(&datagram.encode()).wrap_err("sending")?;
+        }
+        Ok(())
+    }
+}
+
+/// Our end of a networked match.
+#[derive(Debug)]
+pub enum Network {
+    Host(Host),
+    Client(Client),
+    Peer(Box<Peer>),
+}
+
+impl Network {
+    /// Says goodbye to the other end, if there is one, and hangs up.
+    pub fn leave(self) {
+        let _ = match self {
+            Self::Host(mut host) => host.send(&Message::Bye),
+            Self::Client(mut client) => client.connection.send(&Message::Bye),
+            Self::Peer(mut peer) => (0..BYE_REPEATS).try_for_each(|_| peer.send(&Datagram::Bye)),
+        };
+    }
+}
+
+/// This machine's address on the local network, for telling others where to join.
+fn local_address() -> Option<IpAddr> {
+    // connecting a UDP socket sends nothing, but picks the interface packets would leave by
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("192.0.2.1", 9)).ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_unspecified()).then_some(ip)
+}
@@ -0,0 +1,27 @@
+use std::{env, path::PathBuf};
+
+/// Name of the directory the app keeps its files in, inside the XDG base directories.
+const APP_DIR: &str = "pong-tui";
+
+/// The directory for configuration files: `$XDG_CONFIG_HOME/pong-tui`, falling back to
+/// `~/.config/pong-tui`.
+///
+/// Returns `None` if neither variable is set, which leaves nowhere sensible to write to.
+pub fn config_dir() -> Option<PathBuf> {
+    base_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join(APP_DIR))
+}
+
+/// The directory for files the app creates itself, like replays: `$XDG_DATA_HOME/pong-tui`,
+/// falling back to `~/.local/share/pong-tui`.
+pub fn data_dir() -> Option<PathBuf> {
+    base_dir("XDG_DATA_HOME", ".local/share").map(|dir| dir.join(APP_DIR))
+}
+
+/// `$variable` if it holds an absolute path, as the XDG spec requires, otherwise `fallback`
+/// under the home directory.
+fn base_dir(variable: &str, fallback: &str) -> Option<PathBuf> {
+    env::var_os(variable)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+}
@@ -0,0 +1,102 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Widget},
+};
+
+/// An entry of the pause popup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseItem {
+    Resume,
+    Restart,
+    QuitToMenu,
+}
+
+impl PauseItem {
+    pub const ALL: [Self; 3] = [Self::Resume, Self::Restart, Self::QuitToMenu];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Resume => "Resume",
+            Self::Restart => "Restart",
+            Self::QuitToMenu => "Quit to Menu",
+        }
+    }
+}
+
+/// The popup shown over a paused match.
+#[derive(Debug, Default)]
+pub struct PauseMenu {
+    selected: usize,
+    /// Whether the match is hosted by someone else, who alone can restart it.
+    pub remote: bool,
+}
+
+impl PauseMenu {
+    /// The items on offer.
+    fn items(&self) -> &'static [PauseItem] {
+        if self.remote {
+            &[PauseItem::Resume, PauseItem::QuitToMenu]
+        } else {
+            &PauseItem::ALL
+        }
+    }
+
+    pub fn selected(&self) -> PauseItem {
+        self.items()[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.items().len();
+    }
+
+    pub fn previous(&mut self) {
+        let len = self.items().len();
+        self.selected = (self.selected + len - 1) % len;
+    }
+
+    /// Selects the first item again, for the next time the game is paused.
+    pub fn reset(&mut self) {
+        self.selected = 0;
+    }
+}
+
+impl Widget for &PauseMenu {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines: Vec<Line> = self
+            .items()
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                if index == self.selected {
+                    Line::from(format!("▶ {} ◀", item.label()))
+                        .bold()
+                        .reversed()
+                } else {
+                    Line::from(item.label())
+                }
+                .centered()
+            })
+            .collect();
+
+        let title = Line::from(" Paused ").bold().blue().centered();
+        let popup = popup_area(area, 24, lines.len() as u16 + 2);
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .block(Block::bordered().title(title))
+            .render(popup, buf);
+    }
+}
+
+/// A `width` by `height` rectangle in the middle of `area`, shrunk to fit if needed.
+pub fn popup_area(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
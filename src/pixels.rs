@@ -0,0 +1,160 @@
+use std::fmt;
+
+use ratatui::{buffer::Buffer, layout::Rect, style::Style, widgets::Widget};
+use serde::{Deserialize, Serialize};
+
+/// How many pixels each terminal cell is split into when drawing the ball and paddles.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Resolution {
+    /// One pixel per cell, drawn with `█`.
+    Cell,
+    /// One by two pixels per cell, drawn with `▀`, `▄` and `█`.
+    #[default]
+    HalfBlock,
+    /// Two by four pixels per cell, drawn with braille patterns.
+    Braille,
+}
+
+impl Resolution {
+    pub const ALL: [Self; 3] = [Self::Cell, Self::HalfBlock, Self::Braille];
+
+    /// Pixels per cell, horizontally and vertically.
+    fn scale(self) -> (usize, usize) {
+        match self {
+            Self::Cell => (1, 1),
+            Self::HalfBlock => (1, 2),
+            Self::Braille => (2, 4),
+        }
+    }
+
+    /// The next resolution, wrapping around after the last one.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&r| r == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Cell => "Cell",
+            Self::HalfBlock => "Half-block",
+            Self::Braille => "Braille",
+        })
+    }
+}
+
+/// A monochrome bitmap covering a grid of cells at a given [`Resolution`], onto which a logical
+/// world of a fixed size is projected.
+///
+/// Shapes are filled in world units with fractional positions, so something moving a fraction of
+/// a cell can still visibly move. Only cells with at least one pixel set are drawn, leaving
+/// whatever was rendered underneath the empty ones.
+#[derive(Debug)]
+pub struct PixelGrid {
+    resolution: Resolution,
+    width: usize,
+    height: usize,
+    /// Pixels per world unit, horizontally and vertically.
+    scale: (f64, f64),
+    pixels: Vec<bool>,
+    style: Style,
+}
+
+impl PixelGrid {
+    /// An empty grid the size of `area`, showing a world `world_width` by `world_height` units.
+    pub fn new(resolution: Resolution, area: Rect, world_width: f64, world_height: f64) -> Self {
+        let (sx, sy) = resolution.scale();
+        let width = usize::from(area.width) * sx;
+        let height = usize::from(area.height) * sy;
+        Self {
+            resolution,
+            width,
+            height,
+            scale: (width as f64 / world_width, height as f64 / world_height),
+            pixels: vec![false; width * height],
+            style: Style::default(),
+        }
+    }
+
+    /// Sets the style drawn pixels get.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets every pixel of the rectangle at (`x`, `y`) of size `width` by `height`, in world
+    /// units.
+    ///
+    /// Anything outside the grid is clipped; a rectangle inside it covers at least one pixel.
+    pub fn fill(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        let (sx, sy) = self.scale;
+        let (left, right) = pixel_span(x, width, sx, self.width);
+        let (top, bottom) = pixel_span(y, height, sy, self.height);
+        for row in top..bottom {
+            self.pixels[row * self.width + left..row * self.width + right].fill(true);
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+
+    /// The character for the cell at (`column`, `row`), if any of its pixels are set.
+    fn symbol(&self, column: usize, row: usize) -> Option<char> {
+        match self.resolution {
+            Resolution::Cell => self.get(column, row).then_some('█'),
+            Resolution::HalfBlock => {
+                let top = self.get(column, row * 2);
+                let bottom = self.get(column, row * 2 + 1);
+                match (top, bottom) {
+                    (false, false) => None,
+                    (true, false) => Some('▀'),
+                    (false, true) => Some('▄'),
+                    (true, true) => Some('█'),
+                }
+            }
+            Resolution::Braille => {
+                // dot numbering of the unicode braille block, by (x, y) inside the cell
+                const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+                let mut bits = 0;
+                for (dx, column_dots) in DOTS.iter().enumerate() {
+                    for (dy, dot) in column_dots.iter().enumerate() {
+                        if self.get(column * 2 + dx, row * 4 + dy) {
+                            bits |= dot;
+                        }
+                    }
+                }
+                (bits != 0).then(|| char::from_u32(0x2800 + bits).unwrap_or('█'))
+            }
+        }
+    }
+}
+
+/// The range of pixels covered by `start..start + length` (in world units) at `scale` pixels
+/// per unit, clipped to `0..limit`.
+fn pixel_span(start: f64, length: f64, scale: f64, limit: usize) -> (usize, usize) {
+    let first = (start * scale).round();
+    let last = ((start + length) * scale).round().max(first + 1.0);
+    let clip = |value: f64| value.clamp(0.0, limit as f64) as usize;
+    (clip(first), clip(last))
+}
+
+impl Widget for &PixelGrid {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (sx, sy) = self.resolution.scale();
+        let columns = (self.width / sx).min(usize::from(area.width));
+        let rows = (self.height / sy).min(usize::from(area.height));
+        for row in 0..rows {
+            for column in 0..columns {
+                let Some(symbol) = self.symbol(column, row) else {
+                    continue;
+                };
+                let position = (area.x + column as u16, area.y + row as u16);
+                if let Some(cell) = buf.cell_mut(position) {
+                    cell.set_char(symbol).set_style(self.style);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, TableState},
+};
+
+use crate::bindings::{Action, Bindings};
+
+/// The screen listing every [`Action`] with its keys, where they can be rebound.
+///
+/// Its own keys (arrows, Enter, Backspace and Esc) are fixed, so a broken bindings file can
+/// always be repaired from here.
+#[derive(Debug, Default)]
+pub struct RebindScreen {
+    selected: usize,
+    /// Whether the next key press becomes the selected action's binding.
+    capturing: bool,
+    /// The outcome of the last change, shown at the bottom.
+    pub status: Option<String>,
+}
+
+impl RebindScreen {
+    pub fn selected(&self) -> Action {
+        Action::ALL[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % Action::ALL.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = (self.selected + Action::ALL.len() - 1) % Action::ALL.len();
+    }
+
+    pub fn capturing(&self) -> bool {
+        self.capturing
+    }
+
+    pub fn set_capturing(&mut self, capturing: bool) {
+        self.capturing = capturing;
+    }
+
+    pub fn render(&self, bindings: &Bindings, frame: &mut Frame, area: Rect) {
+        let title = Line::from(" Key Bindings ").bold().blue().centered();
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [table_area, status_area, hint_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let rows = Action::ALL.iter().map(|&action| {
+            let keys: Vec<String> = bindings
+                .keys(action)
+                .iter()
+                .map(|k| k.to_string())
+                .collect();
+            Row::new([action.description().to_string(), keys.join(", ")])
+        });
+        let table = Table::new(rows, [Constraint::Length(28), Constraint::Fill(1)])
+            .header(Row::new(["Action", "Keys"]).bold())
+            .row_highlight_style(Style::default().reversed());
+        let mut state = TableState::default().with_selected(self.selected);
+        frame.render_stateful_widget(table, table_area, &mut state);
+
+        if let Some(status) = &self.status {
+            frame.render_widget(Paragraph::new(status.as_str()).centered(), status_area);
+        }
+
+        let hint = if self.capturing {
+            format!(
+                "Press the new key for {} · Esc to cancel",
+                self.selected().description()
+            )
+        } else {
+            "↑/↓ to move · Enter to rebind · Backspace to reset · Esc to go back".to_string()
+        };
+        frame.render_widget(Line::from(hint).dim().centered(), hint_area);
+    }
+}
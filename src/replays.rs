@@ -0,0 +1,328 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, eyre},
+};
+use pong_core::{GameState, Playback, Replay, Score};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, TableState},
+};
+
+use crate::{date, paths};
+
+/// Name of the replay directory inside [`paths::data_dir`].
+const DIR_NAME: &str = "replays";
+
+/// Extension of replay files.
+const EXTENSION: &str = "pongreplay";
+
+/// How many replays are kept; saving another deletes the oldest, apart from those kept for
+/// good with [`keep`].
+const MAX_REPLAYS: usize = 50;
+
+/// End of the name of a replay file kept for good.
+const KEPT_SUFFIX: &str = "-kept";
+
+/// Playback speeds the viewer can switch between.
+const SPEEDS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+/// How far a seek jumps, in seconds of match time.
+const SEEK_STEP: f64 = 5.0;
+
+pub fn dir() -> Result<PathBuf> {
+    paths::data_dir()
+        .map(|dir| dir.join(DIR_NAME))
+        .ok_or_else(|| eyre!("neither XDG_DATA_HOME nor HOME is set"))
+}
+
+/// Writes `replay` to a new file named after the current time, then deletes the oldest replays
+/// beyond [`MAX_REPLAYS`] that aren't kept for good.
+pub fn save(replay: &Replay) -> Result<PathBuf> {
+    let dir = dir()?;
+    fs::create_dir_all(&dir).wrap_err_with(|| format!("creating {dir:?}"))?;
+
+    let now = date::now();
+    let mut path = dir.join(format!("{now}.{EXTENSION}"));
+    for n in 1.. {
+        if !path.exists() {
+            break;
+        }
+        path = dir.join(format!("{now}-{n}.{EXTENSION}"));
+    }
+    fs::write(&path, replay.encode()).wrap_err_with(|| format!("writing {path:?}"))?;
+
+    let mut files = replay_files()?;
+    files.retain(|file| !is_kept(file));
+    files.sort();
+    for old in files.iter().rev().skip(MAX_REPLAYS) {
+        fs::remove_file(old).wrap_err_with(|| format!("removing {old:?}"))?;
+    }
+    Ok(path)
+}
+
+/// Renames the replay saved at `path` so that saving more never deletes it, returning its new
+/// path.
+pub fn keep(path: &Path) -> Result<PathBuf> {
+    if is_kept(path) {
+        return Ok(path.to_path_buf());
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let kept = path.with_file_name(format!("{stem}{KEPT_SUFFIX}.{EXTENSION}"));
+    fs::rename(path, &kept).wrap_err_with(|| format!("renaming {path:?}"))?;
+    Ok(kept)
+}
+
+fn is_kept(path: &Path) -> bool {
+    path.file_stem()
+        .is_some_and(|stem| stem.to_string_lossy().ends_with(KEPT_SUFFIX))
+}
+
+pub fn load(path: &Path) -> Result<Replay> {
+    let bytes = fs::read(path).wrap_err_with(|| format!("reading {path:?}"))?;
+    Replay::decode(&bytes).wrap_err_with(|| format!("reading {path:?}"))
+}
+
+/// Every replay file in [`dir`], in no particular order.
+fn replay_files() -> Result<Vec<PathBuf>> {
+    let dir = dir()?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error).wrap_err_with(|| format!("listing {dir:?}")),
+    };
+    Ok(entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+        .collect())
+}
+
+/// A saved replay, as listed on the Replays screen.
+#[derive(Debug, Clone)]
+pub struct ReplayEntry {
+    pub path: PathBuf,
+    /// When the match was saved, in seconds since the Unix epoch.
+    recorded: u64,
+    players: String,
+    score: Score,
+    /// Length of the match, in seconds.
+    length: f64,
+}
+
+impl ReplayEntry {
+    fn read(path: PathBuf) -> Result<Self> {
+        let replay = load(&path)?;
+        let recorded = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.split('-').next())
+            .and_then(|secs| secs.parse().ok())
+            .unwrap_or_default();
+        Ok(Self {
+            recorded,
+            players: format!("{} vs {}", replay.left, replay.right),
+            score: replay.final_state().score(),
+            length: replay.len() as f64 * replay.dt(),
+            path,
+        })
+    }
+}
+
+/// The Replays screen: saved replays, newest first.
+#[derive(Debug, Default)]
+pub struct ReplayBrowser {
+    entries: Vec<ReplayEntry>,
+    selected: usize,
+    /// Trouble reading the replays, shown at the bottom.
+    pub status: Option<String>,
+}
+
+impl ReplayBrowser {
+    /// Reads every saved replay, skipping and counting any that can't be read.
+    pub fn load() -> Self {
+        let mut browser = Self::default();
+        let files = match replay_files() {
+            Ok(files) => files,
+            Err(error) => {
+                browser.status = Some(format!("{error:#}"));
+                return browser;
+            }
+        };
+        let count = files.len();
+        browser.entries = files
+            .into_iter()
+            .filter_map(|path| ReplayEntry::read(path).ok())
+            .collect();
+        browser
+            .entries
+            .sort_by(|a, b| b.recorded.cmp(&a.recorded).then(b.path.cmp(&a.path)));
+        let unreadable = count - browser.entries.len();
+        if unreadable > 0 {
+            browser.status = Some(format!("{unreadable} replay(s) could not be read"));
+        }
+        browser
+    }
+
+    pub fn selected(&self) -> Option<&ReplayEntry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn next(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + 1) % self.entries.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + self.entries.len() - 1) % self.entries.len();
+        }
+    }
+
+    pub fn render(&self, hint: &str, frame: &mut Frame, area: Rect) {
+        let title = Line::from(" Replays ").bold().blue().centered();
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [table_area, status_area, hint_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        if self.entries.is_empty() {
+            let text = "No replays yet. Every finished match is saved here.";
+            frame.render_widget(Paragraph::new(text).centered(), table_area);
+        } else {
+            let rows = self.entries.iter().map(|entry| {
+                Row::new([
+                    date::format(entry.recorded),
+                    entry.players.clone(),
+                    format!("{} - {}", entry.score.left, entry.score.right),
+                    date::clock(entry.length),
+                ])
+            });
+            let widths = [
+                Constraint::Length(17),
+                Constraint::Fill(1),
+                Constraint::Length(7),
+                Constraint::Length(6),
+            ];
+            let table = Table::new(rows, widths)
+                .header(Row::new(["Date", "Players", "Score", "Length"]).bold())
+                .row_highlight_style(Style::default().reversed());
+            let mut state = TableState::default().with_selected(self.selected);
+            frame.render_stateful_widget(table, table_area, &mut state);
+        }
+
+        if let Some(status) = &self.status {
+            frame.render_widget(Paragraph::new(status.as_str()).centered(), status_area);
+        }
+        frame.render_widget(Line::from(hint).dim().centered(), hint_area);
+    }
+}
+
+/// Plays a replay back in real time or faster, and lets the viewer move around in it.
+#[derive(Debug, Clone)]
+pub struct ReplayViewer {
+    playback: Playback,
+    /// Index into [`SPEEDS`].
+    speed: usize,
+    paused: bool,
+    /// Real time not yet turned into replay ticks, in seconds.
+    clock: f64,
+}
+
+impl ReplayViewer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            playback: Playback::new(replay),
+            speed: SPEEDS.iter().position(|&speed| speed == 1.0).unwrap_or(0),
+            paused: false,
+            clock: 0.0,
+        }
+    }
+
+    pub fn game(&self) -> &GameState {
+        self.playback.game()
+    }
+
+    /// Plays as many ticks as `dt` seconds of real time cover at the current speed.
+    pub fn update(&mut self, dt: f64) {
+        if self.paused {
+            return;
+        }
+        self.clock += dt * SPEEDS[self.speed];
+        let tick = self.playback.replay().dt();
+        while self.clock >= tick && !self.playback.is_finished() {
+            self.playback.step();
+            self.clock -= tick;
+        }
+        if self.playback.is_finished() {
+            self.paused = true;
+            self.clock = 0.0;
+        }
+    }
+
+    /// Pauses or resumes, starting over if the end has been reached.
+    pub fn toggle_pause(&mut self) {
+        if self.paused && self.playback.is_finished() {
+            self.playback.seek(0);
+        }
+        self.paused = !self.paused;
+        self.clock = 0.0;
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    /// Jumps [`SEEK_STEP`] seconds forward or back.
+    pub fn seek(&mut self, forward: bool) {
+        let ticks = (SEEK_STEP / self.playback.replay().dt()) as usize;
+        let tick = if forward {
+            self.playback.tick() + ticks
+        } else {
+            self.playback.tick().saturating_sub(ticks)
+        };
+        self.playback.seek(tick);
+    }
+
+    /// Pauses and moves a single tick forward or back.
+    pub fn step(&mut self, forward: bool) {
+        self.paused = true;
+        let tick = if forward {
+            self.playback.tick() + 1
+        } else {
+            self.playback.tick().saturating_sub(1)
+        };
+        self.playback.seek(tick);
+    }
+
+    /// Where playback is, e.g. `▶ 2x  0:41 / 3:10  Player 1 vs Computer (Hard)`.
+    pub fn status(&self) -> String {
+        let replay = self.playback.replay();
+        let state = if self.paused { "⏸" } else { "▶" };
+        format!(
+            " {state} {}x  {} / {}  {} vs {} ",
+            SPEEDS[self.speed],
+            date::clock(self.playback.tick() as f64 * replay.dt()),
+            date::clock(replay.len() as f64 * replay.dt()),
+            replay.left,
+            replay.right,
+        )
+    }
+}
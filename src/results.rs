@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+
+use pong_core::{BALL_SPEED, GameState, PlayerStats, Side};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table},
+};
+
+use crate::date;
+
+/// An entry of the results screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultsItem {
+    Rematch,
+    Menu,
+    SaveReplay,
+}
+
+impl ResultsItem {
+    pub const ALL: [Self; 3] = [Self::Rematch, Self::Menu, Self::SaveReplay];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Rematch => "Rematch",
+            Self::Menu => "Menu",
+            Self::SaveReplay => "Save Replay",
+        }
+    }
+}
+
+/// The screen shown once a match is over: who won, figures about how it went, and what to do
+/// next.
+#[derive(Debug, Default)]
+pub struct ResultsScreen {
+    selected: usize,
+    /// Whether the match is hosted by someone else, who alone can start a rematch.
+    pub remote: bool,
+    /// The replay saved for the match, if there is one.
+    pub replay: Option<PathBuf>,
+    /// How saving the match went, shown under the figures.
+    pub status: String,
+}
+
+impl ResultsScreen {
+    /// The results of a match that just ended, with the first item selected.
+    pub fn new(remote: bool, replay: Option<PathBuf>, status: String) -> Self {
+        Self {
+            selected: 0,
+            remote,
+            replay,
+            status,
+        }
+    }
+
+    /// The items on offer.
+    fn items(&self) -> Vec<ResultsItem> {
+        ResultsItem::ALL
+            .into_iter()
+            .filter(|&item| match item {
+                ResultsItem::Rematch => !self.remote,
+                ResultsItem::Menu => true,
+                ResultsItem::SaveReplay => self.replay.is_some(),
+            })
+            .collect()
+    }
+
+    pub fn selected(&self) -> ResultsItem {
+        self.items()[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.items().len();
+    }
+
+    pub fn previous(&mut self) {
+        let len = self.items().len();
+        self.selected = (self.selected + len - 1) % len;
+    }
+
+    pub fn render(&self, game: &GameState, hint: &str, frame: &mut Frame, area: Rect) {
+        let title = Line::from(" Results ").bold().blue().centered();
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let items = self.items();
+        let [body, hint_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        let [heading_area, table_area, status_area, items_area] = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Length(7),
+            Constraint::Length(3),
+            Constraint::Length(items.len() as u16),
+        ])
+        .flex(Flex::Center)
+        .areas(body);
+
+        let winner = match game.winner() {
+            Some(Side::Left) => "Left player wins!",
+            Some(Side::Right) => "Right player wins!",
+            None => "No winner",
+        };
+        let score = game.score();
+        let heading = vec![
+            Line::from(winner).bold(),
+            Line::default(),
+            Line::from(format!("{} - {}", score.left, score.right)).bold(),
+        ];
+        frame.render_widget(Paragraph::new(heading).centered(), heading_area);
+
+        let stats = game.stats();
+        let rows = [
+            ["Match length".to_string(), date::clock(stats.duration)],
+            [
+                "Longest rally".to_string(),
+                format!("{} hits", stats.longest_rally),
+            ],
+            ["Paddle hits".to_string(), stats.hits().to_string()],
+            [
+                "Fastest ball".to_string(),
+                format!("{:.1}× serve speed", stats.fastest_ball / BALL_SPEED),
+            ],
+            ["Left accuracy".to_string(), accuracy(stats.left)],
+            ["Right accuracy".to_string(), accuracy(stats.right)],
+            ["Seed".to_string(), game.seed().to_string()],
+        ];
+        let widths = [Constraint::Length(16), Constraint::Length(22)];
+        let [table_area] = Layout::horizontal([Constraint::Length(40)])
+            .flex(Flex::Center)
+            .areas(table_area);
+        frame.render_widget(Table::new(rows.map(Row::new), widths), table_area);
+
+        let status = Paragraph::new(format!("\n{}", self.status)).centered();
+        frame.render_widget(status.dim(), status_area);
+
+        let lines: Vec<Line> = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                if index == self.selected {
+                    Line::from(format!("▶ {} ◀", item.label()))
+                        .bold()
+                        .reversed()
+                } else {
+                    Line::from(item.label())
+                }
+                .centered()
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), items_area);
+
+        frame.render_widget(Line::from(hint).dim().centered(), hint_area);
+    }
+}
+
+/// A player's share of balls returned, along with how many that was.
+fn accuracy(player: PlayerStats) -> String {
+    match player.accuracy() {
+        Some(share) => format!(
+            "{:.0}% ({} of {})",
+            share * 100.0,
+            player.hits,
+            player.hits + player.misses
+        ),
+        None => "-".to_string(),
+    }
+}
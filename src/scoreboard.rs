@@ -0,0 +1,120 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+/// Rows of the big block font, top to bottom, for the digits 0 to 9.
+const FONT: [[&str; 5]; 10] = [
+    ["███", "█ █", "█ █", "█ █", "███"],
+    ["  █", "  █", "  █", "  █", "  █"],
+    ["███", "  █", "███", "█  ", "███"],
+    ["███", "  █", "███", "  █", "███"],
+    ["█ █", "█ █", "███", "  █", "  █"],
+    ["███", "█  ", "███", "  █", "███"],
+    ["███", "█  ", "███", "█ █", "███"],
+    ["███", "  █", "  █", "  █", "  █"],
+    ["███", "█ █", "███", "█ █", "███"],
+    ["███", "█ █", "███", "  █", "███"],
+];
+
+/// Height of a big digit, in rows.
+const DIGIT_HEIGHT: u16 = 5;
+
+/// Shortest playing field that still gets big digits; anything smaller gets plain numbers.
+const MIN_BIG_HEIGHT: u16 = 15;
+
+/// The two scores, drawn in large digits at the top of each half of the field.
+///
+/// Falls back to ordinary single-row digits when the field is too short to spare the room.
+#[derive(Debug, Clone, Copy)]
+pub struct Scoreboard {
+    left: u32,
+    right: u32,
+    style: Style,
+}
+
+impl Scoreboard {
+    pub fn new(left: u32, right: u32) -> Self {
+        Self {
+            left,
+            right,
+            style: Style::default().fg(Color::DarkGray),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Draws `rows` (all the same width) centered horizontally in `area`, one row below its top.
+    /// Spaces are left untouched so the field shows through.
+    fn render_rows(&self, rows: &[String], area: Rect, buf: &mut Buffer) {
+        let width = rows.first().map_or(0, |row| row.chars().count()) as u16;
+        if width > area.width || rows.len() as u16 + 1 > area.height {
+            return;
+        }
+
+        let x = area.x + (area.width - width) / 2;
+        for (dy, row) in rows.iter().enumerate() {
+            let y = area.y + 1 + dy as u16;
+            for (dx, symbol) in row.chars().enumerate() {
+                if symbol == ' ' {
+                    continue;
+                }
+                if let Some(cell) = buf.cell_mut((x + dx as u16, y)) {
+                    cell.set_char(symbol).set_style(self.style);
+                }
+            }
+        }
+    }
+}
+
+/// `value` spelled out in the big font, one string per row.
+fn big_digits(value: u32) -> Vec<String> {
+    let digits: Vec<usize> = value
+        .to_string()
+        .bytes()
+        .map(|digit| usize::from(digit - b'0'))
+        .collect();
+    (0..DIGIT_HEIGHT as usize)
+        .map(|row| {
+            digits
+                .iter()
+                .map(|&digit| FONT[digit][row])
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+impl Widget for Scoreboard {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width < 3 || area.height < 2 {
+            return;
+        }
+
+        // the halves either side of the net drawn by `App::center_line`
+        let center = area.width.saturating_sub(1) / 2;
+        let left = Rect {
+            width: center,
+            ..area
+        };
+        let right = Rect {
+            x: area.x + center + 1,
+            width: area.width - center - 1,
+            ..area
+        };
+
+        for (value, half) in [(self.left, left), (self.right, right)] {
+            let rows = if area.height >= MIN_BIG_HEIGHT {
+                big_digits(value)
+            } else {
+                vec![value.to_string()]
+            };
+            self.render_rows(&rows, half, buf);
+        }
+    }
+}
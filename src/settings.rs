@@ -0,0 +1,202 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, TableState},
+};
+
+use pong_core::Difficulty;
+
+use crate::{
+    config::{Config, INPUT_DELAYS, PERCENTAGES, ROLLBACK_WINDOWS, TARGET_SCORES, TICK_RATES},
+    net::Netcode,
+    pixels::Resolution,
+    theme::Theme,
+};
+
+/// A line of the Settings screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingItem {
+    TargetScore,
+    WinByTwo,
+    BallSpeed,
+    PaddleSize,
+    Difficulty,
+    Theme,
+    TickRate,
+    Resolution,
+    Netcode,
+    InputDelay,
+    RollbackWindow,
+    KeyBindings,
+}
+
+impl SettingItem {
+    pub const ALL: [Self; 12] = [
+        Self::TargetScore,
+        Self::WinByTwo,
+        Self::BallSpeed,
+        Self::PaddleSize,
+        Self::Difficulty,
+        Self::Theme,
+        Self::TickRate,
+        Self::Resolution,
+        Self::Netcode,
+        Self::InputDelay,
+        Self::RollbackWindow,
+        Self::KeyBindings,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::TargetScore => "Target score",
+            Self::WinByTwo => "Win by two",
+            Self::BallSpeed => "Ball speed",
+            Self::PaddleSize => "Paddle size",
+            Self::Difficulty => "Computer difficulty",
+            Self::Theme => "Theme",
+            Self::TickRate => "Tick rate",
+            Self::Resolution => "Drawing resolution",
+            Self::Netcode => "Network play",
+            Self::InputDelay => "Input delay",
+            Self::RollbackWindow => "Rollback window",
+            Self::KeyBindings => "Key bindings",
+        }
+    }
+
+    fn value(self, config: &Config) -> String {
+        match self {
+            Self::TargetScore => config.target_score.to_string(),
+            Self::WinByTwo => if config.win_by_two { "On" } else { "Off" }.to_string(),
+            Self::BallSpeed => format!("{}%", config.ball_speed),
+            Self::PaddleSize => format!("{}%", config.paddle_size),
+            Self::Difficulty => config.difficulty.to_string(),
+            Self::Theme => config.theme.to_string(),
+            Self::TickRate => format!("{} Hz", config.tick_rate),
+            Self::Resolution => config.resolution.to_string(),
+            Self::Netcode => config.netcode.to_string(),
+            Self::InputDelay => format!("{} ticks", config.input_delay),
+            Self::RollbackWindow => format!("{} ticks", config.rollback_window),
+            Self::KeyBindings => "Edit…".to_string(),
+        }
+    }
+
+    /// Changes the setting one step up or down, wrapping around the choices that are a list.
+    pub fn adjust(self, config: &mut Config, forward: bool) {
+        match self {
+            Self::TargetScore => step(
+                &mut config.target_score,
+                1,
+                *TARGET_SCORES.start(),
+                *TARGET_SCORES.end(),
+                forward,
+            ),
+            Self::WinByTwo => config.win_by_two = !config.win_by_two,
+            Self::BallSpeed => step(
+                &mut config.ball_speed,
+                10,
+                *PERCENTAGES.start(),
+                *PERCENTAGES.end(),
+                forward,
+            ),
+            Self::PaddleSize => step(
+                &mut config.paddle_size,
+                10,
+                *PERCENTAGES.start(),
+                *PERCENTAGES.end(),
+                forward,
+            ),
+            Self::Difficulty => {
+                config.difficulty = cycle(&Difficulty::ALL, config.difficulty, forward)
+            }
+            Self::Theme => config.theme = cycle(&Theme::ALL, config.theme, forward),
+            Self::TickRate => step(
+                &mut config.tick_rate,
+                30,
+                *TICK_RATES.start(),
+                *TICK_RATES.end(),
+                forward,
+            ),
+            Self::Resolution => {
+                config.resolution = cycle(&Resolution::ALL, config.resolution, forward)
+            }
+            Self::Netcode => config.netcode = cycle(&Netcode::ALL, config.netcode, forward),
+            Self::InputDelay => step(
+                &mut config.input_delay,
+                1,
+                *INPUT_DELAYS.start(),
+                *INPUT_DELAYS.end(),
+                forward,
+            ),
+            Self::RollbackWindow => step(
+                &mut config.rollback_window,
+                1,
+                *ROLLBACK_WINDOWS.start(),
+                *ROLLBACK_WINDOWS.end(),
+                forward,
+            ),
+            Self::KeyBindings => {}
+        }
+    }
+}
+
+/// Moves `value` by `by` toward `max` or `min`, stopping at either.
+fn step(value: &mut u32, by: u32, min: u32, max: u32, forward: bool) {
+    *value = if forward {
+        value.saturating_add(by).min(max)
+    } else {
+        value.saturating_sub(by).max(min)
+    };
+}
+
+/// The entry of `all` after (or before) `current`, wrapping around at either end.
+pub fn cycle<T: Copy + PartialEq>(all: &[T], current: T, forward: bool) -> T {
+    let index = all.iter().position(|&item| item == current).unwrap_or(0);
+    let index = if forward {
+        (index + 1) % all.len()
+    } else {
+        (index + all.len() - 1) % all.len()
+    };
+    all[index]
+}
+
+/// The Settings screen: every [`SettingItem`] with its current value.
+#[derive(Debug, Default)]
+pub struct SettingsScreen {
+    selected: usize,
+}
+
+impl SettingsScreen {
+    pub fn selected(&self) -> SettingItem {
+        SettingItem::ALL[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % SettingItem::ALL.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = (self.selected + SettingItem::ALL.len() - 1) % SettingItem::ALL.len();
+    }
+
+    pub fn render(&self, config: &Config, hint: &str, frame: &mut Frame, area: Rect) {
+        let title = Line::from(" Settings ").bold().blue().centered();
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [table_area, hint_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+
+        let rows = SettingItem::ALL
+            .iter()
+            .map(|item| Row::new([item.label().to_string(), item.value(config)]));
+        let table = Table::new(rows, [Constraint::Length(24), Constraint::Fill(1)])
+            .row_highlight_style(Style::default().reversed());
+        let mut state = TableState::default().with_selected(self.selected);
+        frame.render_stateful_widget(table, table_area, &mut state);
+
+        frame.render_widget(Paragraph::new(Line::from(hint).dim().centered()), hint_area);
+    }
+}
@@ -0,0 +1,79 @@
+use std::{fmt, str::FromStr};
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+/// A colour scheme for the playing field.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Classic,
+    Phosphor,
+    Amber,
+    Ocean,
+}
+
+/// The colours a [`Theme`] gives each part of the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Ball and paddles.
+    pub foreground: Color,
+    pub net: Color,
+    pub border: Color,
+    pub score: Color,
+}
+
+impl Theme {
+    pub const ALL: [Self; 4] = [Self::Classic, Self::Phosphor, Self::Amber, Self::Ocean];
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Self::Classic => Palette {
+                foreground: Color::Reset,
+                net: Color::Reset,
+                border: Color::Reset,
+                score: Color::DarkGray,
+            },
+            Self::Phosphor => Palette {
+                foreground: Color::LightGreen,
+                net: Color::Green,
+                border: Color::Green,
+                score: Color::Green,
+            },
+            Self::Amber => Palette {
+                foreground: Color::LightYellow,
+                net: Color::Yellow,
+                border: Color::Yellow,
+                score: Color::Yellow,
+            },
+            Self::Ocean => Palette {
+                foreground: Color::White,
+                net: Color::Cyan,
+                border: Color::Blue,
+                score: Color::LightBlue,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Classic => "Classic",
+            Self::Phosphor => "Phosphor",
+            Self::Amber => "Amber",
+            Self::Ocean => "Ocean",
+        })
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown theme {name:?}"))
+    }
+}